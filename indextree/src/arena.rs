@@ -1,11 +1,17 @@
 //! Arena.
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    vec,
+    vec::Vec,
+};
 
 #[cfg(not(feature = "std"))]
 use core::{
-    mem,
+    array,
+    cmp::Ordering,
+    fmt, mem,
     num::NonZeroUsize,
     ops::{Index, IndexMut},
     slice,
@@ -15,27 +21,60 @@ use core::{
 use rayon::prelude::*;
 
 #[cfg(feature = "deser")]
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 #[cfg(feature = "std")]
 use std::{
+    array,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    hash::Hash,
     mem,
     num::NonZeroUsize,
     ops::{Index, IndexMut},
     slice,
 };
 
-use crate::{node::NodeData, Node, NodeId};
+use crate::{
+    id::NodeStamp, node::NodeData, relations::insert_last_unchecked, summary::ChildHeights,
+    FrozenArena, IntervalIndex, Node, NodeBitSet, NodeEdge, NodeError, NodeId, Op, OpRecorder,
+    Snapshot, TreeBuilder, TreeSummary,
+};
+
+#[cfg(feature = "std")]
+use crate::ParseError;
 
-#[derive(PartialEq, Eq, Clone, Debug)]
 #[cfg_attr(feature = "deser", derive(Deserialize, Serialize))]
 /// An `Arena` structure containing certain [`Node`]s.
 ///
+/// Two arenas are equal if they hold the same nodes in the same slots, with
+/// the same free slots and generation. Bookkeeping such as the recorded
+/// operations, the depth cache, the dirty flags and the stamp floor is
+/// ignored, and left out of the `Debug` output.
+///
 /// [`Node`]: struct.Node.html
 pub struct Arena<T> {
     nodes: Vec<Node<T>>,
     first_free_slot: Option<usize>,
     last_free_slot: Option<usize>,
+    /// Incremented each time the arena is cleared.
+    #[cfg_attr(feature = "deser", serde(default))]
+    generation: u32,
+    /// Lowest stamp given to new nodes, kept above the stamps of the slots
+    /// dropped from the storage so that their ids stay removed.
+    #[cfg_attr(feature = "deser", serde(default))]
+    stamp_floor: NodeStamp,
+    /// Structural changes recorded while an `OpRecorder` is alive.
+    #[cfg_attr(feature = "deser", serde(skip))]
+    ops: Option<Vec<Op>>,
+    /// Depths of the nodes by index, maintained while the depth cache is
+    /// enabled.
+    #[cfg_attr(feature = "deser", serde(skip))]
+    depths: Option<Vec<usize>>,
+    /// Nodes marked as dirty.
+    #[cfg_attr(feature = "deser", serde(skip))]
+    dirty: NodeBitSet,
 }
 
 impl<T> Arena<T> {
@@ -50,7 +89,242 @@ impl<T> Arena<T> {
             nodes: Vec::with_capacity(n),
             first_free_slot: None,
             last_free_slot: None,
+            generation: 0,
+            stamp_floor: NodeStamp::default(),
+            ops: None,
+            depths: None,
+            dirty: NodeBitSet::default(),
+        }
+    }
+
+    /// Creates a new `Arena` containing a single node from the given data.
+    ///
+    /// Returns the arena and the ID of the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let (mut arena, root) = Arena::with_root("root");
+    /// let child = root.append_value("child", &mut arena);
+    ///
+    /// assert_eq!(*arena[root].get(), "root");
+    /// assert_eq!(arena[child].parent(), Some(root));
+    /// ```
+    pub fn with_root(data: T) -> (Arena<T>, NodeId) {
+        let mut arena = Self::new();
+        let root = arena.new_node(data);
+        (arena, root)
+    }
+
+    /// Creates a new `Arena` containing a single tree, built by the given
+    /// closure.
+    ///
+    /// The closure receives a [`TreeBuilder`] appending to the root node
+    /// created from `root_data`. Use [`TreeBuilder::reserve`] to pre-size the
+    /// storage when the number of nodes is known ahead of time.
+    ///
+    /// Returns the arena and the ID of the root node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let (arena, root) = Arena::build_with("root", |builder| {
+    ///     builder.reserve(3);
+    ///     builder.append_with("1", |builder| {
+    ///         builder.append("1_1");
+    ///     });
+    ///     builder.append("2");
+    /// });
+    ///
+    /// // arena
+    /// // `-- root
+    /// //     |-- 1
+    /// //     |   `-- 1_1
+    /// //     `-- 2
+    ///
+    /// let data = root.descendants(&arena).map(|id| *arena[id].get());
+    /// assert_eq!(data.collect::<Vec<_>>(), ["root", "1", "1_1", "2"]);
+    /// ```
+    ///
+    /// [`TreeBuilder`]: struct.TreeBuilder.html
+    /// [`TreeBuilder::reserve`]: struct.TreeBuilder.html#method.reserve
+    pub fn build_with<F>(root_data: T, build: F) -> (Arena<T>, NodeId)
+    where
+        F: FnOnce(&mut TreeBuilder<'_, T>),
+    {
+        let mut arena = Self::new();
+        let root = arena.new_node(root_data);
+        build(&mut TreeBuilder::new(&mut arena, root));
+        (arena, root)
+    }
+
+    /// Creates a new `Arena` containing a single tree, generated by
+    /// recursively unfolding the given seed.
+    ///
+    /// `f` expands a seed into the data of its node and the seeds of its
+    /// children. The tree is built breadth-first, and nodes at `max_depth`
+    /// (the root being at depth 0) are not expanded further: their child
+    /// seeds are dropped.
+    ///
+    /// Returns the arena and the ID of the root node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// // Binary tree of the interval bisections.
+    /// let (arena, root) = Arena::unfold((0, 8), 2, |&(start, end)| {
+    ///     let middle = (start + end) / 2;
+    ///     ((start, end), vec![(start, middle), (middle, end)])
+    /// });
+    ///
+    /// // arena
+    /// // `-- (0, 8)
+    /// //     |-- (0, 4)
+    /// //     |   |-- (0, 2)
+    /// //     |   `-- (2, 4)
+    /// //     `-- (4, 8)
+    /// //         |-- (4, 6)
+    /// //         `-- (6, 8)
+    ///
+    /// assert_eq!(root.descendants(&arena).count(), 7);
+    /// let leaves = root
+    ///     .descendants(&arena)
+    ///     .filter(|id| arena[*id].first_child().is_none())
+    ///     .map(|id| *arena[id].get());
+    /// assert_eq!(leaves.collect::<Vec<_>>(), [(0, 2), (2, 4), (4, 6), (6, 8)]);
+    /// ```
+    pub fn unfold<S, F>(seed: S, max_depth: usize, mut f: F) -> (Arena<T>, NodeId)
+    where
+        F: FnMut(&S) -> (T, Vec<S>),
+    {
+        let mut arena = Self::new();
+        let (data, seeds) = f(&seed);
+        let root = arena.new_node(data);
+
+        let mut queue = VecDeque::new();
+        if max_depth > 0 {
+            queue.extend(seeds.into_iter().map(|seed| (root, seed, 1)));
+        }
+        while let Some((parent, seed, depth)) = queue.pop_front() {
+            let (data, seeds) = f(&seed);
+            let id = arena.new_node(data);
+            insert_last_unchecked(&mut arena, id, parent);
+            if depth < max_depth {
+                queue.extend(seeds.into_iter().map(|seed| (id, seed, depth + 1)));
+            }
+        }
+
+        (arena, root)
+    }
+
+    /// Creates a new `Arena` from a sequence of data with the positions of
+    /// their parents.
+    ///
+    /// Each item is a pair of the position of its parent in the sequence
+    /// (`None` for a root) and its data. Parents must precede their children,
+    /// and children are appended in sequence order.
+    ///
+    /// Returns the arena and the IDs of the created nodes, in sequence order.
+    ///
+    /// # Failures
+    ///
+    /// Returns [`NodeError::InvalidParentIndex`] error, carrying the position
+    /// of the offending item, if a parent position does not precede the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let items = vec![(None, "root"), (Some(0), "a"), (Some(1), "a_1"), (Some(0), "b")];
+    /// let (arena, ids) = Arena::try_from_parent_ids(items).unwrap();
+    ///
+    /// // arena
+    /// // `-- root
+    /// //     |-- a
+    /// //     |   `-- a_1
+    /// //     `-- b
+    ///
+    /// assert_eq!(arena[ids[2]].parent(), Some(ids[1]));
+    /// let children = ids[0].children(&arena).map(|id| *arena[id].get());
+    /// assert_eq!(children.collect::<Vec<_>>(), ["a", "b"]);
+    ///
+    /// assert!(Arena::try_from_parent_ids(vec![(Some(1), "a"), (None, "b")]).is_err());
+    /// ```
+    ///
+    /// [`NodeError::InvalidParentIndex`]: enum.NodeError.html#variant.InvalidParentIndex
+    pub fn try_from_parent_ids<I>(items: I) -> Result<(Arena<T>, Vec<NodeId>), NodeError>
+    where
+        I: IntoIterator<Item = (Option<usize>, T)>,
+    {
+        let items = items.into_iter();
+        let mut arena = Self::with_capacity(items.size_hint().0);
+        let mut ids = Vec::with_capacity(items.size_hint().0);
+        for (index, (parent, data)) in items.enumerate() {
+            let parent = match parent {
+                Some(parent) if parent < index => Some(ids[parent]),
+                Some(_) => return Err(NodeError::InvalidParentIndex(index)),
+                None => None,
+            };
+            let id = arena.new_node(data);
+            if let Some(parent) = parent {
+                insert_last_unchecked(&mut arena, id, parent);
+            }
+            ids.push(id);
         }
+
+        Ok((arena, ids))
+    }
+
+    /// Creates a new `Arena` containing a complete `branching`-ary tree of
+    /// the given values, assigned in level order.
+    ///
+    /// Every level is full except possibly the last one, which is filled
+    /// from the left. This gives reproducible balanced trees, e.g. for tests
+    /// and benchmarks.
+    ///
+    /// Returns the arena and the ID of the root node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty, or if `branching` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let (arena, root) = Arena::balanced_from_slice(&[1, 2, 3, 4, 5, 6], 2);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 2
+    /// //     |   |-- 4
+    /// //     |   `-- 5
+    /// //     `-- 3
+    /// //         `-- 6
+    ///
+    /// let data = root.descendants(&arena).map(|id| *arena[id].get());
+    /// assert_eq!(data.collect::<Vec<_>>(), [1, 2, 4, 5, 3, 6]);
+    /// ```
+    pub fn balanced_from_slice(values: &[T], branching: usize) -> (Arena<T>, NodeId)
+    where
+        T: Clone,
+    {
+        assert!(!values.is_empty(), "the values should not be empty");
+        assert!(branching > 0, "the branching factor should not be zero");
+        let mut arena = Self::with_capacity(values.len());
+        let mut ids = Vec::with_capacity(values.len());
+        for (index, value) in values.iter().enumerate() {
+            let id = arena.new_node(value.clone());
+            if index > 0 {
+                insert_last_unchecked(&mut arena, id, ids[(index - 1) / branching]);
+            }
+            ids.push(id);
+        }
+
+        (arena, ids[0])
     }
 
     /// Returns the number of nodes the arena can hold without reallocating.
@@ -96,6 +370,7 @@ impl<T> Arena<T> {
         Some(NodeId::from_non_zero_usize(
             node_id,
             self.nodes[node_index].stamp,
+            self.generation,
         ))
     }
 
@@ -125,7 +400,7 @@ impl<T> Arena<T> {
         self.nodes
             .get(index0)
             .filter(|n| !n.is_removed())
-            .map(|node| NodeId::from_non_zero_usize(index, node.stamp))
+            .map(|node| NodeId::from_non_zero_usize(index, node.stamp, self.generation))
     }
 
     /// Creates a new node from its associated data.
@@ -146,18 +421,90 @@ impl<T> Arena<T> {
     pub fn new_node(&mut self, data: T) -> NodeId {
         let (index, stamp) = if let Some(index) = self.pop_front_free_node() {
             let node = &mut self.nodes[index];
-            node.reuse(data);
+            node.reuse(data, self.stamp_floor);
             (index, node.stamp)
         } else {
             let index = self.nodes.len();
-            let node = Node::new(data);
+            let mut node = Node::new(data);
+            node.stamp = self.stamp_floor;
             let stamp = node.stamp;
             self.nodes.push(node);
             (index, stamp)
         };
+        if let Some(depths) = &mut self.depths {
+            depths.resize(depths.len().max(index + 1), 0);
+            depths[index] = 0;
+        }
         let next_index1 =
             NonZeroUsize::new(index.wrapping_add(1)).expect("Too many nodes in the arena");
-        NodeId::from_non_zero_usize(next_index1, stamp)
+        NodeId::from_non_zero_usize(next_index1, stamp, self.generation)
+    }
+
+    /// Creates a new parent node with a single child, and returns the IDs of
+    /// both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already has `usize::max_value() - 1` nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let (parent, child) = arena.new_node_with_child("parent", "child");
+    ///
+    /// assert_eq!(arena[child].parent(), Some(parent));
+    /// assert_eq!(*arena[child].get(), "child");
+    /// ```
+    pub fn new_node_with_child(&mut self, parent_data: T, child_data: T) -> (NodeId, NodeId) {
+        let parent = self.new_node(parent_data);
+        let child = self.new_node(child_data);
+        self.record(Op::Append { parent, child });
+        insert_last_unchecked(self, child, parent);
+        (parent, child)
+    }
+
+    /// Appends the given values as a chain below `parent`.
+    ///
+    /// The first value becomes the last child of `parent`, and every following
+    /// value becomes the single child of the previous one. Storage for the
+    /// new nodes is reserved up front based on the iterator's size hint.
+    ///
+    /// Returns the ID of the deepest node of the chain, or `parent` itself if
+    /// `values` is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already has `usize::max_value()` nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node(0);
+    /// let deepest = arena.extend_chain(root, 1..4);
+    ///
+    /// // arena
+    /// // `-- 0
+    /// //     `-- 1
+    /// //         `-- 2
+    /// //             `-- 3 *
+    ///
+    /// assert_eq!(*arena[deepest].get(), 3);
+    /// let chain = deepest.ancestors(&arena).map(|id| *arena[id].get());
+    /// assert_eq!(chain.collect::<Vec<_>>(), [3, 2, 1, 0]);
+    /// ```
+    pub fn extend_chain<I: IntoIterator<Item = T>>(&mut self, parent: NodeId, values: I) -> NodeId {
+        let values = values.into_iter();
+        self.reserve(values.size_hint().0);
+        values.fold(parent, |parent, value| {
+            let child = self.new_node(value);
+            self.record(Op::Append { parent, child });
+            insert_last_unchecked(self, child, parent);
+            child
+        })
     }
 
     /// Counts the number of nodes in arena and returns it.
@@ -249,6 +596,284 @@ impl<T> Arena<T> {
         self.nodes.get_mut(id.index0())
     }
 
+    /// Replaces the data of the node with the given id, returning the old
+    /// data.
+    ///
+    /// The node is marked as [dirty].
+    ///
+    /// # Failures
+    ///
+    /// As for [`validate_ids`], returns [`NodeError::NotFound`] if the id is
+    /// out of range of the arena or was handed out before the arena was
+    /// [`clear`]ed, or [`NodeError::RemovedNode`] if the node is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeError};
+    /// let mut arena = Arena::new();
+    /// let foo = arena.new_node("foo");
+    /// assert_eq!(arena.set_data(foo, "FOO!").unwrap(), "foo");
+    /// assert_eq!(*arena[foo].get(), "FOO!");
+    ///
+    /// foo.remove(&mut arena);
+    /// assert!(matches!(
+    ///     arena.set_data(foo, "bar"),
+    ///     Err(NodeError::RemovedNode(id)) if id == foo
+    /// ));
+    /// ```
+    ///
+    /// [`NodeError::NotFound`]: enum.NodeError.html#variant.NotFound
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`validate_ids`]: struct.Arena.html#method.validate_ids
+    /// [`clear`]: struct.Arena.html#method.clear
+    /// [dirty]: struct.NodeId.html#method.mark_dirty
+    pub fn set_data(&mut self, id: NodeId, data: T) -> Result<T, NodeError> {
+        self.validate_ids(&[id])?;
+        self.dirty.set(id);
+        match &mut self[id].data {
+            NodeData::Data(old) => Ok(mem::replace(old, data)),
+            _ => Err(NodeError::RemovedNode(id)),
+        }
+    }
+
+    /// Checks that all the given ids refer to live nodes of the arena.
+    ///
+    /// This allows failing fast, before starting a batch of mutations.
+    ///
+    /// # Failures
+    ///
+    /// Returns an error for the first invalid id:
+    ///
+    /// * [`NodeError::NotFound`] if the id is out of range of the arena, or
+    ///   was handed out before the arena was [`clear`]ed.
+    /// * [`NodeError::RemovedNode`] if the node is removed, even if its slot
+    ///   was reused by another node since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeError};
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n2 = arena.new_node("2");
+    /// let n3 = arena.new_node("3");
+    /// assert!(arena.validate_ids(&[n1, n2, n3]).is_ok());
+    ///
+    /// n2.remove(&mut arena);
+    /// assert!(matches!(
+    ///     arena.validate_ids(&[n1, n2, n3]),
+    ///     Err(NodeError::RemovedNode(id)) if id == n2
+    /// ));
+    /// ```
+    ///
+    /// [`NodeError::NotFound`]: enum.NodeError.html#variant.NotFound
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`clear`]: struct.Arena.html#method.clear
+    pub fn validate_ids(&self, ids: &[NodeId]) -> Result<(), NodeError> {
+        for &id in ids {
+            if id.index0() >= self.nodes.len() || !id.is_current(self) {
+                return Err(NodeError::NotFound(id));
+            }
+            if id.is_removed(self) {
+                return Err(NodeError::RemovedNode(id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns mutable references to the nodes with the given ids at once.
+    ///
+    /// Returns `None` if any of the ids are equal to each other or are not
+    /// available in the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let a = arena.new_node(1);
+    /// let b = arena.new_node(2);
+    /// let c = arena.new_node(3);
+    ///
+    /// let [a_node, b_node, c_node] = arena.get_disjoint_mut([a, b, c]).unwrap();
+    /// std::mem::swap(a_node.get_mut(), c_node.get_mut());
+    /// *b_node.get_mut() *= 10;
+    /// assert_eq!(*arena[a].get(), 3);
+    /// assert_eq!(*arena[b].get(), 20);
+    /// assert_eq!(*arena[c].get(), 1);
+    ///
+    /// assert!(arena.get_disjoint_mut([a, b, a]).is_none());
+    /// ```
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        ids: [NodeId; N],
+    ) -> Option<[&mut Node<T>; N]> {
+        // Visit the ids in storage order so that the slice can be split
+        // successively.
+        let mut order: [usize; N] = array::from_fn(|i| i);
+        order.sort_unstable_by_key(|&i| ids[i].index0());
+        if order
+            .windows(2)
+            .any(|pair| ids[pair[0]].index0() == ids[pair[1]].index0())
+        {
+            return None;
+        }
+        if order
+            .last()
+            .is_some_and(|&i| ids[i].index0() >= self.nodes.len())
+        {
+            return None;
+        }
+
+        let mut nodes: [Option<&mut Node<T>>; N] = array::from_fn(|_| None);
+        let mut rest = self.nodes.as_mut_slice();
+        let mut offset = 0;
+        for i in order {
+            let index0 = ids[i].index0();
+            let (_, tail) = mem::take(&mut rest).split_at_mut(index0 - offset);
+            let (node, tail) = tail
+                .split_first_mut()
+                .expect("index should be within bounds");
+            nodes[i] = Some(node);
+            rest = tail;
+            offset = index0 + 1;
+        }
+
+        Some(nodes.map(|node| node.expect("all nodes should be retrieved")))
+    }
+
+    /// Returns references to the nodes with the given ids, in the same order.
+    ///
+    /// Returns `None` if any of the ids is invalid, as reported by
+    /// [`validate_ids()`]. Unlike [`get_disjoint_mut()`], ids may repeat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let a = arena.new_node(1);
+    /// let b = arena.new_node(2);
+    ///
+    /// let nodes = arena.get_many(&[b, a, b]).unwrap();
+    /// let data = nodes.iter().map(|node| *node.get()).collect::<Vec<_>>();
+    /// assert_eq!(data, [2, 1, 2]);
+    ///
+    /// a.remove(&mut arena);
+    /// assert!(arena.get_many(&[b, a]).is_none());
+    /// ```
+    ///
+    /// [`validate_ids()`]: struct.Arena.html#method.validate_ids
+    /// [`get_disjoint_mut()`]: struct.Arena.html#method.get_disjoint_mut
+    pub fn get_many(&self, ids: &[NodeId]) -> Option<Vec<&Node<T>>> {
+        self.validate_ids(ids).ok()?;
+        Some(ids.iter().map(|&id| &self[id]).collect())
+    }
+
+    /// Returns mutable references to the data of all children of the given
+    /// node at once, in the order of the children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node(1);
+    /// let n1_1 = n1.append_value(10, &mut arena);
+    /// let n1_2 = n1.append_value(20, &mut arena);
+    /// n1_1.append_value(100, &mut arena);
+    ///
+    /// let children = arena.children_data_mut(n1);
+    /// assert_eq!(children.len(), 2);
+    /// for data in children {
+    ///     *data += 1;
+    /// }
+    /// assert_eq!(*arena[n1_1].get(), 11);
+    /// assert_eq!(*arena[n1_2].get(), 21);
+    /// ```
+    pub fn children_data_mut(&mut self, parent: NodeId) -> Vec<&mut T> {
+        let children: Vec<_> = parent.children(self).collect();
+        self.data_mut_in_order(&children)
+    }
+
+    /// Returns mutable references to the data of the given distinct nodes at
+    /// once, in the order of `ids`.
+    pub(crate) fn data_mut_in_order(&mut self, ids: &[NodeId]) -> Vec<&mut T> {
+        // Visit the nodes in storage order so that the slice can be split
+        // successively.
+        let mut order: Vec<(usize, usize)> = ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (id.index0(), position))
+            .collect();
+        order.sort_unstable();
+
+        let mut data: Vec<Option<&mut T>> = Vec::with_capacity(order.len());
+        data.resize_with(order.len(), || None);
+        let mut rest = self.nodes.as_mut_slice();
+        let mut offset = 0;
+        for (index0, position) in order {
+            let (_, tail) = mem::take(&mut rest).split_at_mut(index0 - offset);
+            let (node, tail) = tail
+                .split_first_mut()
+                .expect("index should be within bounds");
+            data[position] = Some(node.get_mut());
+            rest = tail;
+            offset = index0 + 1;
+        }
+
+        data.into_iter()
+            .map(|data| data.expect("all nodes should be retrieved"))
+            .collect()
+    }
+
+    /// Returns an iterator of mutable references to the data of the subtree
+    /// rooted at `root`, in pre-order.
+    ///
+    /// The storage is split as the nodes are visited, so that every step
+    /// hands out a single reference.
+    pub(crate) fn subtree_data_mut(&mut self, root: NodeId) -> impl Iterator<Item = &mut T> {
+        // Parts of the storage not visited yet, by offset.
+        let mut rest = BTreeMap::new();
+        rest.insert(0, self.nodes.as_mut_slice());
+        // Next siblings of the ancestors of the current node.
+        let mut resume: Vec<Option<NodeId>> = Vec::new();
+        let mut cursor = Some(root);
+        core::iter::from_fn(move || {
+            let id = cursor?;
+            let index0 = id.index0();
+            let (&offset, _) = rest
+                .range(..=index0)
+                .next_back()
+                .expect("node should be visited once");
+            let part = rest.remove(&offset).expect("part should exist");
+            let (before, tail) = part.split_at_mut(index0 - offset);
+            let (node, after) = tail
+                .split_first_mut()
+                .expect("index should be within bounds");
+            if !before.is_empty() {
+                rest.insert(offset, before);
+            }
+            if !after.is_empty() {
+                rest.insert(index0 + 1, after);
+            }
+
+            let next_sibling = if id == root { None } else { node.next_sibling };
+            cursor = match node.first_child {
+                Some(first_child) => {
+                    resume.push(next_sibling);
+                    Some(first_child)
+                }
+                // Resume from the closest ancestor with a next sibling.
+                None => {
+                    next_sibling.or_else(|| core::iter::from_fn(|| resume.pop()).flatten().next())
+                }
+            };
+            Some(node.get_mut())
+        })
+    }
+
     /// Returns an iterator of all nodes in the arena in storage-order.
     ///
     /// Note that this iterator returns also removed elements, which can be
@@ -282,7 +907,7 @@ impl<T> Arena<T> {
     /// ```
     ///
     /// [`is_removed()`]: struct.Node.html#method.is_removed
-    pub fn iter(&self) -> slice::Iter<Node<T>> {
+    pub fn iter(&self) -> slice::Iter<'_, Node<T>> {
         self.nodes.iter()
     }
 
@@ -309,57 +934,1113 @@ impl<T> Arena<T> {
     /// assert_eq!(node_refs, vec![5, 6]);
     /// ```
     /// [`is_removed()`]: struct.Node.html#method.is_removed
-    pub fn iter_mut(&mut self) -> slice::IterMut<Node<T>> {
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Node<T>> {
         self.nodes.iter_mut()
     }
 
-    /// Clears all the nodes in the arena, but retains its allocated capacity.
+    /// Applies the given function to the data of every live node, in
+    /// storage-order.
     ///
-    /// Note that this does not marks all nodes as removed, but completely
-    /// removes them from the arena storage, thus invalidating all the node ids
-    /// that were previously created.
+    /// Removed nodes are skipped.
     ///
-    /// Any attempt to call the [`is_removed()`] method on the node id will
-    /// result in panic behavior.
+    /// # Examples
     ///
-    /// [`is_removed()`]: struct.NodeId.html#method.is_removed
-    pub fn clear(&mut self) {
-        self.nodes.clear();
-        self.first_free_slot = None;
-        self.last_free_slot = None;
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let foo = arena.new_node(1);
+    /// let bar = arena.new_node(2);
+    /// let baz = arena.new_node(3);
+    /// bar.remove(&mut arena);
+    ///
+    /// arena.map_data_in_place(|data| *data *= 10);
+    /// assert_eq!(*arena[foo].get(), 10);
+    /// assert_eq!(*arena[baz].get(), 30);
+    /// ```
+    pub fn map_data_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T),
+    {
+        for node in &mut self.nodes {
+            if let NodeData::Data(data) = &mut node.data {
+                f(data);
+            }
+        }
     }
 
-    /// Returns a slice of the inner nodes collection.
+    /// Returns an iterator of the IDs of the root nodes, i.e. the live nodes
+    /// without parent, in storage-order.
     ///
-    /// Note that this **does not** return root elements, it simply
-    /// returns a slice into the internal representation of the arena.
-    pub fn as_slice(&self) -> &[Node<T>] {
-        self.nodes.as_slice()
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let _n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n2 = arena.new_node("2");
+    ///
+    /// let mut iter = arena.roots();
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .filter(|node| !node.is_removed() && node.parent.is_none())
+            .filter_map(move |node| self.get_node_id(node))
     }
 
-    pub(crate) fn free_node(&mut self, id: NodeId) {
-        let node = &mut self[id];
-        node.data = NodeData::NextFree(None);
-        node.stamp.as_removed();
-        let stamp = node.stamp;
-        if stamp.reuseable() {
-            if let Some(index) = self.last_free_slot {
-                let new_last = id.index0();
-                self.nodes[index].data = NodeData::NextFree(Some(new_last));
-                self.last_free_slot = Some(new_last);
-            } else {
-                debug_assert!(self.first_free_slot.is_none());
-                debug_assert!(self.last_free_slot.is_none());
-                self.first_free_slot = Some(id.index0());
-                self.last_free_slot = Some(id.index0());
-            }
-        }
+    /// Returns an iterator of the IDs of all live nodes in tree-order.
+    ///
+    /// Each tree of the forest is traversed in pre-order (as
+    /// [`NodeId::descendants`]), and the trees are visited in the order of
+    /// [`roots()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n2 = arena.new_node("2");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    ///
+    /// let mut iter = arena.iter_tree_order();
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`NodeId::descendants`]: struct.NodeId.html#method.descendants
+    /// [`roots()`]: struct.Arena.html#method.roots
+    pub fn iter_tree_order(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.roots().flat_map(move |root| root.descendants(self))
     }
 
-    fn pop_front_free_node(&mut self) -> Option<usize> {
+    /// Compares two nodes by their position in the pre-order traversal of the
+    /// forest, as given by [`iter_tree_order()`].
+    ///
+    /// This can be used to sort IDs into document order. Unlike
+    /// [`NodeId::preorder_index`], it only walks the ancestors of both nodes
+    /// and the siblings between their branches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the nodes is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let mut ids = vec![n1_2, n1_1_1, n1, n1_1];
+    /// ids.sort_by(|&a, &b| arena.cmp_preorder(a, b));
+    /// assert_eq!(ids, &[n1, n1_1, n1_1_1, n1_2]);
+    /// ```
+    ///
+    /// [`iter_tree_order()`]: struct.Arena.html#method.iter_tree_order
+    /// [`NodeId::preorder_index`]: struct.NodeId.html#method.preorder_index
+    pub fn cmp_preorder(&self, a: NodeId, b: NodeId) -> Ordering {
+        assert!(
+            !a.is_removed(self) && !b.is_removed(self),
+            "the nodes should not be removed"
+        );
+        if a == b {
+            return Ordering::Equal;
+        }
+
+        // Paths from the roots down to the nodes.
+        let mut path_a: Vec<_> = a.ancestors(self).collect();
+        path_a.reverse();
+        let mut path_b: Vec<_> = b.ancestors(self).collect();
+        path_b.reverse();
+
+        match path_a.iter().zip(&path_b).position(|(a, b)| a != b) {
+            // Different trees: roots are visited in storage order.
+            Some(0) => path_a[0].index0().cmp(&path_b[0].index0()),
+            // Sibling branches under a common ancestor.
+            Some(depth) => {
+                let (branch_a, branch_b) = (path_a[depth], path_b[depth]);
+                if branch_a.following_siblings(self).any(|id| id == branch_b) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            // One node is an ancestor of the other.
+            None => path_a.len().cmp(&path_b.len()),
+        }
+    }
+
+    /// Returns the lowest common ancestor of all the given nodes.
+    ///
+    /// A node counts as its own ancestor, so the lowest common ancestor of a
+    /// single node is the node itself. Returns `None` if `nodes` is empty or
+    /// if the nodes are not all part of the same tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_1_2 = arena.new_node("1_1_2");
+    /// # n1_1.append(n1_1_2, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n2 = arena.new_node("2");
+    /// #
+    /// // arena
+    /// // |-- 1
+    /// // |   |-- 1_1
+    /// // |   |   |-- 1_1_1
+    /// // |   |   `-- 1_1_2
+    /// // |   `-- 1_2
+    /// // `-- 2
+    ///
+    /// assert_eq!(arena.common_ancestor(&[n1_1_1, n1_1_2]), Some(n1_1));
+    /// assert_eq!(arena.common_ancestor(&[n1_1_1, n1_2]), Some(n1));
+    /// assert_eq!(arena.common_ancestor(&[n1_1, n1_1_2]), Some(n1_1));
+    /// assert_eq!(arena.common_ancestor(&[n1_2, n2]), None);
+    /// assert_eq!(arena.common_ancestor(&[]), None);
+    /// ```
+    pub fn common_ancestor(&self, nodes: &[NodeId]) -> Option<NodeId> {
+        let (first, rest) = nodes.split_first()?;
+        // Path from the root down to the common ancestor found so far.
+        let mut common: Vec<_> = first.ancestors(self).collect();
+        common.reverse();
+        for node in rest {
+            let mut path: Vec<_> = node.ancestors(self).collect();
+            path.reverse();
+            let shared = common.iter().zip(&path).take_while(|(a, b)| a == b).count();
+            common.truncate(shared);
+        }
+        common.last().copied()
+    }
+
+    /// Returns an iterator of the IDs of the live nodes that are part of a
+    /// multi-node tree, in storage-order.
+    ///
+    /// Isolated nodes, i.e. nodes without parent and children, are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let _scratch = arena.new_node("scratch");
+    ///
+    /// let mut iter = arena.non_trivial_nodes();
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn non_trivial_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .filter(|node| !node.is_removed())
+            .filter(|node| node.parent.is_some() || node.first_child.is_some())
+            .filter_map(move |node| self.get_node_id(node))
+    }
+
+    /// Clears all the nodes in the arena, but retains its allocated capacity.
+    ///
+    /// Note that this does not marks all nodes as removed, but completely
+    /// removes them from the arena storage, thus invalidating all the node ids
+    /// that were previously created.
+    ///
+    /// Any attempt to call the [`is_removed()`] method on the node id will
+    /// result in panic behavior. Previously created ids can be detected with
+    /// [`NodeId::is_current()`].
+    ///
+    /// [`is_removed()`]: struct.NodeId.html#method.is_removed
+    /// [`NodeId::is_current()`]: struct.NodeId.html#method.is_current
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.first_free_slot = None;
+        self.last_free_slot = None;
+        self.generation = self.generation.wrapping_add(1);
+        self.dirty = NodeBitSet::default();
+    }
+
+    /// Starts recording the structural changes of the arena.
+    ///
+    /// While the returned [`OpRecorder`] is alive, appending, prepending,
+    /// inserting, detaching and removing nodes through it are logged as
+    /// [`Op`]s. Changes that leave the tree as is are not logged, and the
+    /// ones made by other methods, e.g. swapping or reordering siblings, are
+    /// logged as the equivalent sequence of [`Op`]s. Creating nodes is not
+    /// logged. The recorder dereferences to the arena, and recording stops
+    /// when it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, Op};
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = arena.new_node("1_1");
+    ///
+    /// let mut recorder = arena.record_ops();
+    /// n1.append(n1_1, &mut recorder);
+    /// n1_1.detach(&mut recorder);
+    /// assert_eq!(
+    ///     recorder.ops(),
+    ///     &[Op::Append { parent: n1, child: n1_1 }, Op::Detach(n1_1)]
+    /// );
+    /// ```
+    ///
+    /// [`OpRecorder`]: struct.OpRecorder.html
+    /// [`Op`]: enum.Op.html
+    pub fn record_ops(&mut self) -> OpRecorder<'_, T> {
+        OpRecorder::new(self)
+    }
+
+    pub(crate) fn start_recording(&mut self) {
+        self.ops = Some(Vec::new());
+    }
+
+    pub(crate) fn stop_recording(&mut self) {
+        self.ops = None;
+    }
+
+    pub(crate) fn recorded_ops(&self) -> &[Op] {
+        self.ops.as_deref().unwrap_or_default()
+    }
+
+    pub(crate) fn take_recorded_ops(&mut self) -> Vec<Op> {
+        self.ops.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    /// Enables the depth cache, making [`NodeId::depth`] O(1).
+    ///
+    /// The depth of every node is computed once, then kept up to date by
+    /// the mutation methods: whenever a node changes parent, the depths of
+    /// its whole subtree are shifted. This adds a cost proportional to the
+    /// size of the moved subtrees to every structural mutation, so it pays
+    /// off for mostly static trees whose depths are queried often.
+    ///
+    /// Enabling the cache again does nothing. The cache is not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// arena.enable_depth_cache();
+    /// assert_eq!(n1_1.depth(&arena), 1);
+    ///
+    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    /// assert_eq!(n1_1_1.depth(&arena), 2);
+    ///
+    /// n1_1.detach(&mut arena);
+    /// assert_eq!(n1_1_1.depth(&arena), 1);
+    /// ```
+    ///
+    /// [`NodeId::depth`]: struct.NodeId.html#method.depth
+    pub fn enable_depth_cache(&mut self) {
+        if self.depths.is_some() {
+            return;
+        }
+        let mut depths = vec![0; self.nodes.len()];
+        for root in self.roots() {
+            let mut depth = 0;
+            for edge in root.traverse(self) {
+                match edge {
+                    NodeEdge::Start(id) => {
+                        depths[id.index0()] = depth;
+                        depth += 1;
+                    }
+                    NodeEdge::End(_) => depth -= 1,
+                }
+            }
+        }
+        self.depths = Some(depths);
+    }
+
+    /// Disables the depth cache, removing its cost from the mutation
+    /// methods.
+    pub fn disable_depth_cache(&mut self) {
+        self.depths = None;
+    }
+
+    /// Returns the cached depth of the given node, if the depth cache is
+    /// enabled.
+    pub(crate) fn cached_depth(&self, id: NodeId) -> Option<usize> {
+        self.depths.as_ref().map(|depths| depths[id.index0()])
+    }
+
+    /// Updates the cached depths of the subtree of the given node after its
+    /// parent changed.
+    pub(crate) fn update_cached_depths(&mut self, id: NodeId) {
+        let Some(depths) = &self.depths else {
+            return;
+        };
+        let old = depths[id.index0()];
+        let new = self[id]
+            .parent
+            .map_or(0, |parent| depths[parent.index0()] + 1);
+        if new == old {
+            return;
+        }
+        let subtree: Vec<_> = id.descendants(self).collect();
+        let depths = self.depths.as_mut().expect("the depth cache is enabled");
+        for node in subtree {
+            depths[node.index0()] = depths[node.index0()] - old + new;
+        }
+    }
+
+    /// Marks the given node as dirty.
+    pub(crate) fn set_dirty(&mut self, id: NodeId) {
+        self.dirty.set(id);
+    }
+
+    /// Returns `true` if the given node is marked as dirty.
+    pub(crate) fn contains_dirty(&self, id: NodeId) -> bool {
+        self.dirty.contains(id)
+    }
+
+    /// Logs the operation if the arena is being recorded.
+    pub(crate) fn is_recording(&self) -> bool {
+        self.ops.is_some()
+    }
+
+    pub(crate) fn record(&mut self, op: Op) {
+        if let Some(ops) = &mut self.ops {
+            ops.push(op);
+        }
+    }
+
+    /// Returns the current generation of the arena, incremented each time the
+    /// arena is cleared.
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Removes the trailing removed nodes from the arena storage, and returns
+    /// the number of reclaimed slots.
+    ///
+    /// Live nodes are not moved, so their ids stay valid. The ids of the
+    /// reclaimed nodes must not be used until their slots are allocated
+    /// again, as accessing them panics. They stay removed afterwards: the new
+    /// nodes get stamps that were never handed out for these slots.
+    ///
+    /// Unlike [`clear()`], this does not bump the generation of the arena:
+    /// the generation is shared by all the nodes, so bumping it would make
+    /// the ids of the live nodes look stale to [`NodeId::is_current()`] too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let foo = arena.new_node("foo");
+    /// let bar = arena.new_node("bar");
+    /// let baz = arena.new_node("baz");
+    /// bar.remove(&mut arena);
+    /// baz.remove(&mut arena);
+    /// assert_eq!(arena.count(), 3);
+    ///
+    /// assert_eq!(arena.trim_tail(), 2);
+    /// assert_eq!(arena.count(), 1);
+    /// assert_eq!(*arena[foo].get(), "foo");
+    ///
+    /// let qux = arena.new_node("qux");
+    /// assert!(bar.is_removed(&arena));
+    /// assert_ne!(bar, qux);
+    /// ```
+    ///
+    /// [`clear()`]: struct.Arena.html#method.clear
+    /// [`NodeId::is_current()`]: struct.NodeId.html#method.is_current
+    pub fn trim_tail(&mut self) -> usize {
+        let len = self
+            .nodes
+            .iter()
+            .rposition(|node| !node.is_removed())
+            .map_or(0, |index| index + 1);
+        self.truncate_removed(len)
+    }
+
+    /// Drops the removed nodes from `len` onwards from the storage, and
+    /// returns their number.
+    fn truncate_removed(&mut self, len: usize) -> usize {
+        let trimmed = self.nodes.len() - len;
+        if trimmed == 0 {
+            return 0;
+        }
+
+        // Unlink the reclaimed slots from the free list, preserving the order
+        // of the remaining ones.
+        let mut cursor = self.first_free_slot.take();
+        self.last_free_slot = None;
+        while let Some(index) = cursor {
+            if let Some(next_free) = self.nodes[index].data.next_free() {
+                cursor = next_free;
+            } else {
+                unreachable!("A data node consider as a freed node");
+            }
+            if index < len {
+                self.push_back_free_node(index);
+            }
+        }
+        for node in &self.nodes[len..] {
+            self.stamp_floor = self.stamp_floor.max(node.stamp.next());
+        }
+        self.nodes.truncate(len);
+
+        trimmed
+    }
+
+    /// Removes the tree rooted at the given node and reclaims the trailing
+    /// storage slots, returning the number of removed nodes.
+    ///
+    /// This is [`NodeId::remove_subtree`] followed by a [`trim_tail()`]
+    /// limited to the nodes of the tree: if the tree occupied the end of the
+    /// storage, e.g. because it was the most recently built one, its slots
+    /// are released and [`count()`] decreases. Other removed nodes are left
+    /// in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// n1.append_value("1_1", &mut arena);
+    /// let n2 = arena.new_node("2");
+    /// n2.append_value("2_1", &mut arena);
+    /// n2.append_value("2_2", &mut arena);
+    /// assert_eq!(arena.count(), 5);
+    ///
+    /// assert_eq!(arena.drop_tree(n2), 3);
+    /// assert_eq!(arena.count(), 2);
+    /// ```
+    ///
+    /// [`NodeId::remove_subtree`]: struct.NodeId.html#method.remove_subtree
+    /// [`trim_tail()`]: struct.Arena.html#method.trim_tail
+    /// [`count()`]: struct.Arena.html#method.count
+    pub fn drop_tree(&mut self, root: NodeId) -> usize {
+        let mut freed = root
+            .descendants(self)
+            .map(NodeId::index0)
+            .collect::<Vec<_>>();
+        let removed = root.remove_subtree_count(self);
+
+        freed.sort_unstable();
+        let mut len = self.nodes.len();
+        while len > 0 && freed.last() == Some(&(len - 1)) {
+            freed.pop();
+            len -= 1;
+        }
+        self.truncate_removed(len);
+        removed
+    }
+
+    /// Removes every live node that is not part of the subtree of `root`.
+    ///
+    /// `root` is detached from its parent if it has one, so that it becomes
+    /// the only root of the arena. The slots of the removed nodes are
+    /// reclaimed for reuse, while the capacity of the arena is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root` is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    /// let n2 = arena.new_node("2");
+    ///
+    /// arena.keep_only(n1_1);
+    /// assert_eq!(arena.roots().collect::<Vec<_>>(), &[n1_1]);
+    /// assert_eq!(n1_1.descendants(&arena).collect::<Vec<_>>(), &[n1_1, n1_1_1]);
+    /// assert!(n1.is_removed(&arena));
+    /// assert!(n2.is_removed(&arena));
+    /// ```
+    pub fn keep_only(&mut self, root: NodeId) {
+        assert!(!root.is_removed(self), "the root should not be removed");
+        root.detach(self);
+        let others: Vec<_> = self.roots().filter(|&id| id != root).collect();
+        for other in others {
+            other.remove_subtree(self);
+        }
+    }
+
+    /// Removes every live leaf node, i.e. every node without children, and
+    /// returns their IDs in storage-order.
+    ///
+    /// The leaves are determined before any removal, so a parent whose
+    /// children are all removed by this call is kept, and becomes a leaf for
+    /// the next call. Isolated nodes are leaves too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    /// let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    ///
+    /// assert_eq!(arena.remove_leaves(), &[n1_1, n1_2_1]);
+    /// assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2]);
+    ///
+    /// assert_eq!(arena.remove_leaves(), &[n1_2]);
+    /// assert_eq!(arena.remove_leaves(), &[n1]);
+    /// assert!(arena.remove_leaves().is_empty());
+    /// ```
+    pub fn remove_leaves(&mut self) -> Vec<NodeId> {
+        let leaves: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|node| !node.is_removed() && node.first_child.is_none())
+            .filter_map(|node| self.get_node_id(node))
+            .collect();
+        for &leaf in &leaves {
+            leaf.remove(self);
+        }
+        leaves
+    }
+
+    /// Repeatedly removes the nodes satisfying `should_remove` until no node
+    /// does.
+    ///
+    /// Each pass evaluates `should_remove` on every live node, then
+    /// [`remove`]s the matching ones, splicing their children into their
+    /// parents. Since removing a node may make others match (e.g. a container
+    /// losing its last child), passes are repeated until one removes
+    /// nothing. Every pass but the last one removes at least one node, so
+    /// this always terminates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("div");
+    /// let div = root.append_value("div", &mut arena);
+    /// div.append_value("div", &mut arena);
+    /// let text = root.append_value("text", &mut arena);
+    ///
+    /// // Remove empty non-root containers.
+    /// arena.prune_to_fixpoint(|id, arena| {
+    ///     *arena[id].get() == "div"
+    ///         && arena[id].parent().is_some()
+    ///         && arena[id].first_child().is_none()
+    /// });
+    /// assert_eq!(root.descendants(&arena).collect::<Vec<_>>(), &[root, text]);
+    /// ```
+    ///
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn prune_to_fixpoint<F>(&mut self, should_remove: F)
+    where
+        F: Fn(NodeId, &Arena<T>) -> bool,
+    {
+        loop {
+            let matching: Vec<_> = self
+                .nodes
+                .iter()
+                .filter(|node| !node.is_removed())
+                .filter_map(|node| self.get_node_id(node))
+                .filter(|&id| should_remove(id, self))
+                .collect();
+            if matching.is_empty() {
+                break;
+            }
+            for id in matching {
+                id.remove(self);
+            }
+        }
+    }
+
+    /// Returns a slice of the inner nodes collection.
+    ///
+    /// Note that this **does not** return root elements, it simply
+    /// returns a slice into the internal representation of the arena.
+    pub fn as_slice(&self) -> &[Node<T>] {
+        self.nodes.as_slice()
+    }
+
+    /// Computes the Euler-tour intervals of all nodes in the arena.
+    ///
+    /// The returned [`IntervalIndex`] answers "is this node in the subtree of
+    /// that node" queries in O(1). It is a snapshot of the current structure
+    /// and is invalidated by any subsequent mutation of the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n2 = arena.new_node("2");
+    ///
+    /// let index = arena.compute_intervals();
+    /// assert!(index.is_descendant(n1, n1_1));
+    /// assert!(!index.is_descendant(n2, n1_1));
+    /// ```
+    ///
+    /// [`IntervalIndex`]: struct.IntervalIndex.html
+    pub fn compute_intervals(&self) -> IntervalIndex {
+        IntervalIndex::new(self)
+    }
+
+    /// Creates an empty [`NodeBitSet`] sized for the nodes of the arena.
+    ///
+    /// The set marks nodes with a single bit each, which makes it a cheap
+    /// replacement for per-node flags in algorithms over large arenas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    ///
+    /// let mut visited = arena.node_bitset();
+    /// visited.set(n1_1);
+    /// assert!(visited.contains(n1_1));
+    /// assert!(!visited.contains(n1));
+    /// ```
+    ///
+    /// [`NodeBitSet`]: struct.NodeBitSet.html
+    pub fn node_bitset(&self) -> NodeBitSet {
+        NodeBitSet::new(self.count())
+    }
+
+    /// Returns an iterator of the IDs of the [dirty] nodes, in storage-order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    ///
+    /// n1_2.mark_dirty(&mut arena);
+    /// arena.set_data(n1, "one").unwrap();
+    /// assert_eq!(arena.dirty_nodes().collect::<Vec<_>>(), &[n1, n1_2]);
+    /// ```
+    ///
+    /// [dirty]: struct.NodeId.html#method.mark_dirty
+    pub fn dirty_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.dirty.iter(self)
+    }
+
+    /// Unmarks all the [dirty] nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// n1.mark_dirty(&mut arena);
+    ///
+    /// arena.clear_dirty();
+    /// assert!(!n1.is_dirty(&arena));
+    /// assert_eq!(arena.dirty_nodes().next(), None);
+    /// ```
+    ///
+    /// [dirty]: struct.NodeId.html#method.mark_dirty
+    pub fn clear_dirty(&mut self) {
+        self.dirty = NodeBitSet::default();
+    }
+
+    /// Saves the current state of the arena, to roll back to with
+    /// [`restore`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let snapshot = arena.snapshot();
+    ///
+    /// n1_1.remove(&mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    ///
+    /// arena.restore(&snapshot);
+    /// assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_1]);
+    /// assert!(n1_2.is_removed(&arena));
+    /// ```
+    ///
+    /// [`restore`]: #method.restore
+    pub fn snapshot(&self) -> Snapshot<T>
+    where
+        T: Clone,
+    {
+        Snapshot::new(self.clone())
+    }
+
+    /// Rolls the arena back to the given saved state.
+    ///
+    /// The storage of the arena is reused where possible. The snapshot is
+    /// left untouched, so that it can be restored again, e.g. to retry a
+    /// failed batch of edits.
+    ///
+    /// The IDs of the nodes created since the snapshot was taken stay
+    /// removed, even once their slots are allocated again.
+    ///
+    /// The depth cache stays enabled or disabled as it was before restoring,
+    /// and is recomputed if needed.
+    ///
+    /// See [`snapshot`](#method.snapshot).
+    pub fn restore(&mut self, snapshot: &Snapshot<T>)
+    where
+        T: Clone,
+    {
+        let depth_cache = self.depths.is_some();
+        // Keep the stamps handed out since the snapshot from being reused.
+        let stamp_floor = self
+            .nodes
+            .iter()
+            .map(|node| node.stamp.next())
+            .fold(self.stamp_floor, NodeStamp::max);
+        self.clone_from(snapshot.arena());
+        self.stamp_floor = self.stamp_floor.max(stamp_floor);
+        if !depth_cache {
+            self.disable_depth_cache();
+        } else if self.depths.is_none() {
+            self.enable_depth_cache();
+        }
+    }
+
+    /// Freezes the arena into an immutable, traversal-optimized snapshot.
+    ///
+    /// Removed nodes are dropped and the remaining ones are laid out in tree
+    /// order, with their subtree sizes precomputed. The nodes keep their IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n2 = arena.new_node("2");
+    ///
+    /// let frozen = arena.freeze();
+    /// assert_eq!(frozen.get(n1_1), Some(&"1_1"));
+    /// assert!(frozen.is_descendant(n1, n1_1));
+    /// assert!(!frozen.is_descendant(n2, n1_1));
+    /// ```
+    pub fn freeze(self) -> FrozenArena<T> {
+        FrozenArena::new(self)
+    }
+
+    /// Groups the live nodes whose subtrees are equal, both in structure and
+    /// in data.
+    ///
+    /// Two subtrees are equal if their roots have equal data and their
+    /// children subtrees are pairwise equal, in order. Every live node belongs
+    /// to exactly one group. Groups are ordered by their first node, and the
+    /// nodes within a group are in [`iter_tree_order()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("root");
+    /// let a = root.append_value("x", &mut arena);
+    /// let a_1 = a.append_value("y", &mut arena);
+    /// let b = root.append_value("x", &mut arena);
+    /// let b_1 = b.append_value("y", &mut arena);
+    /// let c = root.append_value("x", &mut arena);
+    ///
+    /// assert_eq!(
+    ///     arena.group_isomorphic_subtrees(),
+    ///     vec![vec![root], vec![a, b], vec![a_1, b_1], vec![c]]
+    /// );
+    /// ```
+    ///
+    /// [`iter_tree_order()`]: struct.Arena.html#method.iter_tree_order
+    #[cfg(feature = "std")]
+    pub fn group_isomorphic_subtrees(&self) -> Vec<Vec<NodeId>>
+    where
+        T: Hash + Eq,
+    {
+        // Assign a class to each subtree bottom-up, identified by the data of
+        // its root and the classes of its children.
+        let mut classes: HashMap<(&T, Vec<usize>), usize> = HashMap::new();
+        let mut class_of = vec![None; self.nodes.len()];
+        for root in self.roots() {
+            for edge in root.traverse(self) {
+                if let NodeEdge::End(id) = edge {
+                    let children = id
+                        .children(self)
+                        .map(|child| {
+                            class_of[child.index0()].expect("children should be visited first")
+                        })
+                        .collect();
+                    let next_class = classes.len();
+                    let class = *classes
+                        .entry((self[id].get(), children))
+                        .or_insert(next_class);
+                    class_of[id.index0()] = Some(class);
+                }
+            }
+        }
+
+        let mut group_of = vec![None; classes.len()];
+        let mut groups: Vec<Vec<NodeId>> = Vec::new();
+        for id in self.iter_tree_order() {
+            let class = class_of[id.index0()].expect("live nodes should have a class");
+            let group = *group_of[class].get_or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(id);
+        }
+        groups
+    }
+
+    /// Returns a map from the ID of every live node to the IDs of its
+    /// children, in insertion order.
+    ///
+    /// Leaves are mapped to an empty `Vec`. This is a bridge to generic
+    /// graph algorithms working on adjacency lists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    ///
+    /// let adjacency = arena.adjacency();
+    /// assert_eq!(adjacency.len(), 3);
+    /// assert_eq!(adjacency[&n1], &[n1_1, n1_2]);
+    /// assert!(adjacency[&n1_1].is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn adjacency(&self) -> HashMap<NodeId, Vec<NodeId>> {
+        self.nodes
+            .iter()
+            .filter(|node| !node.is_removed())
+            .filter_map(|node| self.get_node_id(node))
+            .map(|id| (id, id.children(self).collect()))
+            .collect()
+    }
+
+    /// Serializes the live forest in a canonical form, independent of the
+    /// allocation history of the arena.
+    ///
+    /// The nodes are emitted as a sequence of `(parent, data)` pairs, where
+    /// `parent` is the position of the parent within the sequence. Each tree
+    /// is emitted in pre-order, and the trees are sorted by their own
+    /// `(parent, data)` sequences, with parent positions relative to the
+    /// tree, so that neither their storage-order nor the order in which they
+    /// were created matters. Storage positions and removed nodes are ignored,
+    /// so structurally equal forests serialize identically. The output can be
+    /// deserialized as `Vec<(Option<usize>, T)>` and fed to
+    /// [`try_from_parent_ids()`].
+    ///
+    /// This can be used with `#[serde(serialize_with = "Arena::serialize_canonical")]`.
+    ///
+    /// [`try_from_parent_ids()`]: struct.Arena.html#method.try_from_parent_ids
+    #[cfg(feature = "deser")]
+    pub fn serialize_canonical<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Ord,
+        S: Serializer,
+    {
+        serializer.collect_seq(self.canonical_nodes())
+    }
+
+    /// Returns the `(parent, data)` pairs of the live nodes in canonical
+    /// order, as serialized by `serialize_canonical`.
+    #[cfg(feature = "deser")]
+    fn canonical_nodes(&self) -> Vec<(Option<usize>, &T)>
+    where
+        T: Ord,
+    {
+        let mut positions = vec![None; self.nodes.len()];
+        let mut trees = Vec::new();
+        for root in self.roots() {
+            let mut tree = Vec::new();
+            for id in root.descendants(self) {
+                let parent = match self[id].parent {
+                    Some(parent) if id != root => Some(
+                        positions[parent.index0()].expect("parent should precede its children"),
+                    ),
+                    _ => None,
+                };
+                positions[id.index0()] = Some(tree.len());
+                tree.push((parent, self[id].get()));
+            }
+            trees.push(tree);
+        }
+        trees.sort();
+
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for tree in trees {
+            let offset = nodes.len();
+            nodes.extend(
+                tree.into_iter()
+                    .map(|(parent, data)| (parent.map(|parent| parent + offset), data)),
+            );
+        }
+        nodes
+    }
+
+    /// Computes a structural summary of the tree rooted at the given node in a
+    /// single traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, TreeSummary};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     |-- 1_2
+    /// //     `-- 1_3
+    ///
+    /// assert_eq!(
+    ///     arena.tree_summary(n1),
+    ///     TreeSummary {
+    ///         node_count: 5,
+    ///         leaf_count: 3,
+    ///         height: 2,
+    ///         max_branching: 3,
+    ///         is_balanced: true,
+    ///     }
+    /// );
+    /// ```
+    pub fn tree_summary(&self, root: NodeId) -> TreeSummary {
+        let mut summary = TreeSummary {
+            node_count: 0,
+            leaf_count: 0,
+            height: 0,
+            max_branching: 0,
+            is_balanced: true,
+        };
+        let mut stack: Vec<ChildHeights> = Vec::new();
+        for edge in root.traverse(self) {
+            match edge {
+                NodeEdge::Start(_) => stack.push(ChildHeights::default()),
+                NodeEdge::End(_) => {
+                    let heights = stack.pop().expect("each end should match a start");
+                    summary.node_count += 1;
+                    summary.max_branching = summary.max_branching.max(heights.children);
+                    if heights.children == 0 {
+                        summary.leaf_count += 1;
+                    }
+                    summary.is_balanced &= heights.is_balanced();
+                    match stack.last_mut() {
+                        Some(parent) => parent.push(heights.height()),
+                        None => summary.height = heights.height(),
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+
+    pub(crate) fn free_node(&mut self, id: NodeId) {
+        self.dirty.clear(id);
+        let node = &mut self[id];
+        node.data.free();
+        node.stamp.as_removed();
+        let stamp = node.stamp;
+        if stamp.reuseable() {
+            self.push_back_free_node(id.index0());
+        }
+    }
+
+    /// Moves the data out of the node and frees it.
+    pub(crate) fn take_node(&mut self, id: NodeId) -> T {
+        let data = match mem::replace(&mut self[id].data, NodeData::NextFree(None)) {
+            NodeData::Data(data) => data,
+            _ => unreachable!("A freed node consider as a data node"),
+        };
+        self.free_node(id);
+        data
+    }
+
+    /// Consumes the arena, returning the data of every node by index, `None`
+    /// for removed nodes.
+    pub(crate) fn into_data(self) -> Vec<Option<T>> {
+        self.nodes
+            .into_iter()
+            .map(|node| match node.data {
+                NodeData::Data(data) => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn push_back_free_node(&mut self, index: usize) {
+        self.nodes[index].data.set_next_free(None);
+        if let Some(last) = self.last_free_slot {
+            self.nodes[last].data.set_next_free(Some(index));
+            self.last_free_slot = Some(index);
+        } else {
+            debug_assert!(self.first_free_slot.is_none());
+            debug_assert!(self.last_free_slot.is_none());
+            self.first_free_slot = Some(index);
+            self.last_free_slot = Some(index);
+        }
+    }
+
+    fn pop_front_free_node(&mut self) -> Option<usize> {
         let first = self.first_free_slot.take();
         if let Some(index) = first {
-            if let NodeData::NextFree(next_free) = self.nodes[index].data {
+            if let Some(next_free) = self.nodes[index].data.next_free() {
                 self.first_free_slot = next_free;
             } else {
                 unreachable!("A data node consider as a freed node");
@@ -373,6 +2054,97 @@ impl<T> Arena<T> {
     }
 }
 
+#[cfg(feature = "std")]
+impl Arena<String> {
+    /// Parses an S-expression into a new arena.
+    ///
+    /// Each node is written as `(data children...)`, where `data` may be
+    /// empty and the children are themselves nodes. Whitespace is allowed
+    /// around the data and between the nodes. A backslash escapes the next
+    /// character, so that data can contain parentheses, backslashes and
+    /// whitespace. This is the format produced by [`NodeId::to_sexpr`].
+    ///
+    /// Returns the arena and the ID of the root node.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`ParseError::Empty`] error if the input does not contain
+    ///   any node.
+    /// * Returns [`ParseError::Unclosed`] error if the input ends before all
+    ///   nodes are closed.
+    /// * Returns [`ParseError::Unexpected`] error if the input contains a
+    ///   character out of place, e.g. an unmatched closing parenthesis, data
+    ///   outside of a node, or a second root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let (arena, root) = Arena::from_sexpr("(root (child1) (child2 (grandchild)))").unwrap();
+    ///
+    /// let mut iter = root.descendants(&arena).map(|id| arena[id].get().as_str());
+    /// assert_eq!(iter.next(), Some("root"));
+    /// assert_eq!(iter.next(), Some("child1"));
+    /// assert_eq!(iter.next(), Some("child2"));
+    /// assert_eq!(iter.next(), Some("grandchild"));
+    /// assert_eq!(iter.next(), None);
+    ///
+    /// assert!(Arena::from_sexpr("(root (child)").is_err());
+    /// ```
+    ///
+    /// [`NodeId::to_sexpr`]: struct.NodeId.html#method.to_sexpr
+    /// [`ParseError::Empty`]: enum.ParseError.html#variant.Empty
+    /// [`ParseError::Unclosed`]: enum.ParseError.html#variant.Unclosed
+    /// [`ParseError::Unexpected`]: enum.ParseError.html#variant.Unexpected
+    pub fn from_sexpr(s: &str) -> Result<(Self, NodeId), ParseError> {
+        let mut arena = Arena::new();
+        let mut root = None;
+        // Nodes that are open, from the root down to the innermost one.
+        let mut open: Vec<NodeId> = Vec::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '(' => {
+                    if root.is_some() && open.is_empty() {
+                        return Err(ParseError::Unexpected(position));
+                    }
+                    while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+                    let mut data = String::new();
+                    while let Some(&(_, c)) = chars.peek() {
+                        if matches!(c, '(' | ')') || c.is_whitespace() {
+                            break;
+                        }
+                        chars.next();
+                        if c == '\\' {
+                            let (_, escaped) = chars.next().ok_or(ParseError::Unclosed)?;
+                            data.push(escaped);
+                        } else {
+                            data.push(c);
+                        }
+                    }
+                    let node = match open.last() {
+                        Some(&parent) => parent.append_value(data, &mut arena),
+                        None => *root.insert(arena.new_node(data)),
+                    };
+                    open.push(node);
+                }
+                ')' => {
+                    if open.pop().is_none() {
+                        return Err(ParseError::Unexpected(position));
+                    }
+                }
+                c if c.is_whitespace() => {}
+                _ => return Err(ParseError::Unexpected(position)),
+            }
+        }
+
+        if !open.is_empty() {
+            return Err(ParseError::Unclosed);
+        }
+        root.map(|root| (arena, root)).ok_or(ParseError::Empty)
+    }
+}
+
 #[cfg(feature = "par_iter")]
 impl<T: Sync> Arena<T> {
     /// Returns an parallel iterator over the whole arena.
@@ -392,10 +2164,79 @@ impl<T> Default for Arena<T> {
             nodes: Vec::new(),
             first_free_slot: None,
             last_free_slot: None,
+            generation: 0,
+            stamp_floor: NodeStamp::default(),
+            ops: None,
+            depths: None,
+            dirty: NodeBitSet::default(),
         }
     }
 }
 
+impl<T: Clone> Clone for Arena<T> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            first_free_slot: self.first_free_slot,
+            last_free_slot: self.last_free_slot,
+            generation: self.generation,
+            stamp_floor: self.stamp_floor,
+            ops: None,
+            depths: self.depths.clone(),
+            dirty: self.dirty.clone(),
+        }
+    }
+
+    /// Performs copy-assignment from `source`, reusing the existing storage
+    /// of `self` instead of allocating a new one where possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node(1);
+    /// root.append_value(2, &mut arena);
+    ///
+    /// let mut snapshot = Arena::with_capacity(16);
+    /// let capacity = snapshot.capacity();
+    /// snapshot.clone_from(&arena);
+    /// assert_eq!(snapshot, arena);
+    /// assert_eq!(snapshot.capacity(), capacity);
+    /// ```
+    fn clone_from(&mut self, source: &Self) {
+        self.nodes.clone_from(&source.nodes);
+        self.first_free_slot = source.first_free_slot;
+        self.last_free_slot = source.last_free_slot;
+        self.generation = source.generation;
+        self.stamp_floor = source.stamp_floor;
+        self.depths.clone_from(&source.depths);
+        self.dirty.clone_from(&source.dirty);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("nodes", &self.nodes)
+            .field("first_free_slot", &self.first_free_slot)
+            .field("last_free_slot", &self.last_free_slot)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Arena<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+            && self.first_free_slot == other.first_free_slot
+            && self.last_free_slot == other.last_free_slot
+            && self.generation == other.generation
+    }
+}
+
+impl<T: Eq> Eq for Arena<T> {}
+
 impl<T> Index<NodeId> for Arena<T> {
     type Output = Node<T>;
 
@@ -428,6 +2269,25 @@ fn reuse_node() {
     assert_eq!(arena.nodes.len(), 3);
 }
 
+#[test]
+fn trim_tail_free_list() {
+    let mut arena = Arena::new();
+    let ids = (0..6).map(|i| arena.new_node(i)).collect::<Vec<_>>();
+    ids[4].remove(&mut arena);
+    ids[1].remove(&mut arena);
+    ids[5].remove(&mut arena);
+    ids[2].remove(&mut arena);
+    assert_eq!(arena.trim_tail(), 2);
+    assert_eq!(arena.nodes.len(), 4);
+    assert_eq!(arena.first_free_slot, Some(1));
+    assert_eq!(arena.last_free_slot, Some(2));
+    assert_eq!(arena.new_node(10).index0(), 1);
+    assert_eq!(arena.new_node(11).index0(), 2);
+    assert_eq!(arena.new_node(12).index0(), 4);
+    assert_eq!(arena.first_free_slot, None);
+    assert_eq!(arena.last_free_slot, None);
+}
+
 #[test]
 fn conserve_capacity() {
     let mut arena = Arena::with_capacity(5);
@@ -447,3 +2307,46 @@ fn conserve_capacity() {
     assert_eq!(arena.count(), 3);
     assert_eq!(arena.capacity(), cap);
 }
+
+#[cfg(feature = "deser")]
+#[test]
+fn canonical_nodes() {
+    let mut first = Arena::new();
+    let root = first.new_node("root");
+    let a = root.append_value("a", &mut first);
+    a.append_value("a_1", &mut first);
+    root.append_value("b", &mut first);
+
+    // Same shape, different allocation order and a tombstone.
+    let mut second = Arena::new();
+    let b = second.new_node("b");
+    let removed = second.new_node("removed");
+    let a_1 = second.new_node("a_1");
+    let root = second.new_node("root");
+    removed.remove(&mut second);
+    let a = second.new_node("a");
+    root.append(b, &mut second);
+    b.insert_before(a, &mut second);
+    a.append(a_1, &mut second);
+
+    let expected = [
+        (None, &"root"),
+        (Some(0), &"a"),
+        (Some(1), &"a_1"),
+        (Some(0), &"b"),
+    ];
+    assert_eq!(first.canonical_nodes(), expected);
+    assert_eq!(second.canonical_nodes(), expected);
+
+    // The trees of a forest are sorted, parent positions being shifted
+    // accordingly.
+    second.new_node("a");
+    let expected = [
+        (None, &"a"),
+        (None, &"root"),
+        (Some(1), &"a"),
+        (Some(2), &"a_1"),
+        (Some(1), &"b"),
+    ];
+    assert_eq!(second.canonical_nodes(), expected);
+}
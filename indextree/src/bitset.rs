@@ -0,0 +1,105 @@
+//! Compact set of nodes.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use core::num::NonZeroUsize;
+
+use crate::{Arena, NodeId};
+
+/// Number of bits in a word.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A compact set of nodes of an [`Arena`], stored as one bit per node.
+///
+/// Membership is keyed by the index of the nodes only: marking a node marks
+/// its storage slot, so a node created in the slot of a removed marked node
+/// is considered marked as well. Clear the bits of removed nodes, or create
+/// a new set, if slots may be reused in between.
+///
+/// Created by [`Arena::node_bitset`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::node_bitset`]: struct.Arena.html#method.node_bitset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodeBitSet {
+    /// Bits indexed by the zero-based node index.
+    words: Vec<u64>,
+}
+
+impl NodeBitSet {
+    /// Creates an empty set with room for `len` nodes.
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Returns the word index and mask of the bit of the given node.
+    fn position(id: NodeId) -> (usize, u64) {
+        let index = id.index0();
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+
+    /// Marks the given node.
+    ///
+    /// The set grows as needed for nodes created after it.
+    pub fn set(&mut self, id: NodeId) {
+        let (word, mask) = Self::position(id);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= mask;
+    }
+
+    /// Unmarks the given node.
+    pub fn clear(&mut self, id: NodeId) {
+        let (word, mask) = Self::position(id);
+        if let Some(word) = self.words.get_mut(word) {
+            *word &= !mask;
+        }
+    }
+
+    /// Returns `true` if the given node is marked.
+    pub fn contains(&self, id: NodeId) -> bool {
+        let (word, mask) = Self::position(id);
+        self.words.get(word).is_some_and(|word| word & mask != 0)
+    }
+
+    /// Returns an iterator of the IDs of the marked live nodes of the arena,
+    /// in storage-order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n2 = arena.new_node("2");
+    /// let n3 = arena.new_node("3");
+    ///
+    /// let mut marked = arena.node_bitset();
+    /// marked.set(n3);
+    /// marked.set(n1);
+    /// assert!(marked.contains(n1));
+    /// assert!(!marked.contains(n2));
+    ///
+    /// let mut iter = marked.iter(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a, T>(&'a self, arena: &'a Arena<T>) -> impl Iterator<Item = NodeId> + 'a {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| {
+                (0..WORD_BITS)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index * WORD_BITS + bit)
+            })
+            .filter_map(move |index| {
+                NonZeroUsize::new(index + 1).and_then(|index1| arena.get_node_id_at(index1))
+            })
+    }
+}
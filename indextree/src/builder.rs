@@ -0,0 +1,65 @@
+//! Tree builder.
+
+use crate::{relations::insert_last_unchecked, Arena, NodeId};
+
+/// A builder appending nodes below a particular node of an [`Arena`].
+///
+/// Created by [`Arena::build_with`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::build_with`]: struct.Arena.html#method.build_with
+pub struct TreeBuilder<'a, T> {
+    /// Arena the nodes are created in.
+    arena: &'a mut Arena<T>,
+    /// Node the new children are appended to.
+    node: NodeId,
+}
+
+impl<'a, T> TreeBuilder<'a, T> {
+    /// Creates a new builder appending to the given node.
+    pub(crate) fn new(arena: &'a mut Arena<T>, node: NodeId) -> Self {
+        Self { arena, node }
+    }
+
+    /// Returns the ID of the node the builder appends to.
+    pub fn id(&self) -> NodeId {
+        self.node
+    }
+
+    /// Returns a reference to the arena being built.
+    pub fn arena(&self) -> &Arena<T> {
+        self.arena
+    }
+
+    /// Reserves capacity for `additional` more nodes to be inserted.
+    ///
+    /// See [`Arena::reserve`].
+    ///
+    /// [`Arena::reserve`]: struct.Arena.html#method.reserve
+    pub fn reserve(&mut self, additional: usize) {
+        self.arena.reserve(additional);
+    }
+
+    /// Creates a new node from its associated data and appends it as the last
+    /// child of the current node.
+    ///
+    /// Returns the ID of the new node.
+    pub fn append(&mut self, data: T) -> NodeId {
+        let child = self.arena.new_node(data);
+        insert_last_unchecked(self.arena, child, self.node);
+        child
+    }
+
+    /// Creates a new node from its associated data, appends it as the last
+    /// child of the current node, and builds its children using `build`.
+    ///
+    /// Returns the ID of the new node.
+    pub fn append_with<F>(&mut self, data: T, build: F) -> NodeId
+    where
+        F: FnOnce(&mut TreeBuilder<'_, T>),
+    {
+        let child = self.append(data);
+        build(&mut TreeBuilder::new(self.arena, child));
+        child
+    }
+}
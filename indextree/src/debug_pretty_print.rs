@@ -5,7 +5,7 @@ use core::fmt::{self, Write as _};
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 #[cfg(feature = "std")]
-use std::vec::Vec;
+use std::{mem, vec::Vec};
 
 use crate::{
     arena::Arena,
@@ -80,9 +80,9 @@ enum LineState {
 }
 
 /// Indent writer for the debug printer.
-struct IndentWriter<'a, 'b> {
-    /// Backend formatter.
-    fmt: &'b mut fmt::Formatter<'a>,
+struct IndentWriter<W> {
+    /// Backend writer.
+    fmt: W,
     /// State of the line writing.
     line_state: LineState,
     /// Indents.
@@ -91,10 +91,10 @@ struct IndentWriter<'a, 'b> {
     pending_ws_only_indent_level: usize,
 }
 
-impl<'a, 'b> IndentWriter<'a, 'b> {
+impl<W: fmt::Write> IndentWriter<W> {
     /// Creates a new `PadAdapter`.
     #[inline]
-    fn new(fmt: &'b mut fmt::Formatter<'a>) -> Self {
+    fn new(fmt: W) -> Self {
         Self {
             fmt,
             line_state: LineState::BeforeIndent,
@@ -175,9 +175,25 @@ impl<'a, 'b> IndentWriter<'a, 'b> {
 
         Ok(())
     }
+
+    /// Writes the indent of the current line if not written yet, even if no
+    /// content follows.
+    #[cfg(feature = "std")]
+    fn write_indent(&mut self) -> fmt::Result {
+        if self.line_state == LineState::BeforeIndent {
+            self.write_indent_partial()?;
+            self.line_state = LineState::PartialIndent;
+        }
+        if self.line_state == LineState::PartialIndent {
+            self.complete_partial_indent()?;
+            self.line_state = LineState::Content;
+        }
+
+        Ok(())
+    }
 }
 
-impl fmt::Write for IndentWriter<'_, '_> {
+impl<W: fmt::Write> fmt::Write for IndentWriter<W> {
     fn write_str(&mut self, mut s: &str) -> fmt::Result {
         while !s.is_empty() {
             // There remains something to print.
@@ -233,91 +249,217 @@ pub struct DebugPrettyPrint<'a, T> {
     id: &'a NodeId,
     /// Arena the node belongs to.
     arena: &'a Arena<T>,
+    /// Maximum number of children printed per node.
+    max_children: Option<usize>,
 }
 
 impl<'a, T> DebugPrettyPrint<'a, T> {
     /// Creates a new `DebugPrettyPrint` object for the node.
     #[inline]
     pub(crate) fn new(id: &'a NodeId, arena: &'a Arena<T>) -> Self {
-        Self { id, arena }
+        Self {
+            id,
+            arena,
+            max_children: None,
+        }
+    }
+
+    /// Prints at most `n` children per node.
+    ///
+    /// The remaining children and their descendants are replaced by a single
+    /// `... (k more)` item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node(0);
+    /// for i in 1..=5 {
+    ///     root.append_value(i, &mut arena);
+    /// }
+    ///
+    /// let printable = root.debug_pretty_print(&arena).max_children(2);
+    /// let expected = r#"0
+    /// |-- 1
+    /// |-- 2
+    /// `-- ... (3 more)"#;
+    /// assert_eq!(printable.to_string(), expected);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn max_children(mut self, n: usize) -> Self {
+        self.max_children = Some(n);
+        self
     }
 }
 
 impl<T: fmt::Display> fmt::Display for DebugPrettyPrint<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let is_alternate = f.alternate();
-        let mut writer = IndentWriter::new(f);
-        let mut traverser = self.id.traverse(self.arena);
+        write_tree(
+            f,
+            *self.id,
+            self.arena,
+            self.max_children,
+            |writer, data| {
+                if is_alternate {
+                    write!(writer, "{:#}", data)
+                } else {
+                    write!(writer, "{}", data)
+                }
+            },
+        )
+    }
+}
 
-        // Print the first (root) node.
-        traverser.next();
-        {
-            let data = self.arena[*self.id].get();
-            if is_alternate {
-                write!(writer, "{:#}", data)?
-            } else {
-                write!(writer, "{}", data)?
-            }
-        }
+impl<T: fmt::Debug> fmt::Debug for DebugPrettyPrint<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_alternate = f.alternate();
+        write_tree(
+            f,
+            *self.id,
+            self.arena,
+            self.max_children,
+            |writer, data| {
+                if is_alternate {
+                    write!(writer, "{:#?}", data)
+                } else {
+                    write!(writer, "{:?}", data)
+                }
+            },
+        )
+    }
+}
 
-        // Print the descendants.
-        while let Some(id) = prepare_next_node_printing(&mut writer, &mut traverser)? {
-            let data = traverser.arena()[id].get();
-            if is_alternate {
-                write!(writer, "{:#}", data)?
-            } else {
-                write!(writer, "{}", data)?
-            }
-        }
+/// Tree printer using a custom closure to render the node data.
+///
+/// This is provided mainly for debugging purpose. Note that the output format
+/// is not guaranteed to be stable, and any format changes won't be considered
+/// as breaking changes.
+///
+/// For usage and output examples, see
+/// [`NodeId::pretty_print_with`][`crate::NodeId::pretty_print_with`] method.
+#[derive(Clone, Copy)]
+pub struct PrettyPrintWith<'a, T, F> {
+    /// Root node ID of the (sub)tree to print.
+    id: NodeId,
+    /// Arena the node belongs to.
+    arena: &'a Arena<T>,
+    /// Renderer of the node data.
+    render: F,
+}
 
-        Ok(())
+impl<'a, T, F> PrettyPrintWith<'a, T, F>
+where
+    F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    /// Creates a new `PrettyPrintWith` object for the node.
+    #[inline]
+    pub(crate) fn new(id: NodeId, arena: &'a Arena<T>, render: F) -> Self {
+        Self { id, arena, render }
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for DebugPrettyPrint<'_, T> {
+impl<T, F> fmt::Display for PrettyPrintWith<'_, T, F>
+where
+    F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let is_alternate = f.alternate();
-        let mut writer = IndentWriter::new(f);
-        let mut traverser = self.id.traverse(self.arena);
+        /// Adapter to render the data using the closure.
+        struct Render<'a, T, F>(&'a T, &'a F);
 
-        // Print the first (root) node.
-        traverser.next();
+        impl<T, F> fmt::Display for Render<'_, T, F>
+        where
+            F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
         {
-            let data = self.arena[*self.id].get();
-            if is_alternate {
-                write!(writer, "{:#?}", data)?
-            } else {
-                write!(writer, "{:?}", data)?
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.1)(self.0, f)
             }
         }
 
-        // Print the descendants.
-        while let Some(id) = prepare_next_node_printing(&mut writer, &mut traverser)? {
-            let data = traverser.arena()[id].get();
-            if is_alternate {
-                write!(writer, "{:#?}", data)?
-            } else {
-                write!(writer, "{:?}", data)?
-            }
-        }
+        write_tree(f, self.id, self.arena, None, |writer, data| {
+            write!(writer, "{}", Render(data, &self.render))
+        })
+    }
+}
 
-        Ok(())
+/// Item to print next.
+enum Item {
+    /// A node.
+    Node(NodeId),
+    /// The number of children omitted.
+    More(usize),
+}
+
+/// Position of an opened node among its siblings.
+struct Open {
+    /// Index of the node among its siblings.
+    index: usize,
+    /// Number of children opened so far.
+    children: usize,
+}
+
+/// Writes the (sub)tree rooted at `id`, rendering each node data with
+/// `write_data`.
+///
+/// If `max_children` is given, at most that many children are printed per
+/// node.
+fn write_tree<T, W: fmt::Write>(
+    f: W,
+    id: NodeId,
+    arena: &Arena<T>,
+    max_children: Option<usize>,
+    mut write_data: impl FnMut(&mut IndentWriter<W>, &T) -> fmt::Result,
+) -> fmt::Result {
+    let mut writer = IndentWriter::new(f);
+    let mut traverser = id.traverse(arena);
+    let mut opened = Vec::new();
+
+    // Print the first (root) node.
+    traverser.next();
+    opened.push(Open {
+        index: 0,
+        children: 0,
+    });
+    write_data(&mut writer, arena[id].get())?;
+
+    // Print the descendants.
+    while let Some(item) =
+        prepare_next_node_printing(&mut writer, &mut traverser, &mut opened, max_children)?
+    {
+        match item {
+            Item::Node(id) => write_data(&mut writer, traverser.arena()[id].get())?,
+            Item::More(count) => write!(writer, "... ({} more)", count)?,
+        }
     }
+
+    Ok(())
 }
 
 /// Prepares printing of next node.
 ///
 /// Internally, this searches next node open and adjust indent level and prefix.
-fn prepare_next_node_printing<T>(
-    writer: &mut IndentWriter<'_, '_>,
+/// Children beyond `max_children` are skipped along with their subtrees, and
+/// reported once as [`Item::More`].
+fn prepare_next_node_printing<T, W: fmt::Write>(
+    writer: &mut IndentWriter<W>,
     traverser: &mut Traverse<'_, T>,
-) -> Result<Option<NodeId>, fmt::Error> {
+    opened: &mut Vec<Open>,
+    max_children: Option<usize>,
+) -> Result<Option<Item>, fmt::Error> {
+    let max_children = max_children.unwrap_or(usize::MAX);
     // Not using `for ev in traverser` in order to access to `traverser`
     // directly in the loop.
     while let Some(ev) = traverser.next() {
         let id = match ev {
             NodeEdge::Start(id) => id,
             NodeEdge::End(_) => {
+                let closed = opened.pop().expect("each end should match a start");
+                if closed.index > max_children {
+                    // Closed a skipped node.
+                    continue;
+                }
                 if writer.close_item().is_ok() {
                     // Closed a non-root node.
                     continue;
@@ -327,11 +469,74 @@ fn prepare_next_node_printing<T>(
                 }
             }
         };
+        let parent = opened
+            .last_mut()
+            .expect("non-root nodes should have a parent");
+        let index = parent.children;
+        parent.children += 1;
+        opened.push(Open { index, children: 0 });
+
+        if index >= max_children {
+            traverser.skip_subtree(id);
+            if index > max_children {
+                continue;
+            }
+            writer.open_item(true)?;
+            let count = id.following_siblings(traverser.arena()).count();
+            return Ok(Some(Item::More(count)));
+        }
+
         let is_last_sibling = traverser.arena()[id].next_sibling().is_none();
         writer.open_item(is_last_sibling)?;
 
-        return Ok(Some(id));
+        return Ok(Some(Item::Node(id)));
     }
 
     Ok(None)
 }
+
+/// Returns an iterator of the lines of the (sub)tree rooted at `id`, as
+/// printed by [`DebugPrettyPrint`] with the `Display` implementation of the
+/// node data, one per node.
+#[cfg(feature = "std")]
+pub(crate) fn pretty_lines<T: fmt::Display>(
+    id: NodeId,
+    arena: &Arena<T>,
+) -> impl Iterator<Item = String> + '_ {
+    /// Message of the `Display` errors, as for `ToString`.
+    const DISPLAY_ERROR: &str = "a Display implementation returned an error unexpectedly";
+
+    let mut writer = IndentWriter::new(String::new());
+    let mut traverser = id.traverse(arena);
+    let mut opened = Vec::new();
+    core::iter::from_fn(move || {
+        let id = if opened.is_empty() {
+            // The first (root) node.
+            traverser.next()?;
+            opened.push(Open {
+                index: 0,
+                children: 0,
+            });
+            id
+        } else {
+            match prepare_next_node_printing(&mut writer, &mut traverser, &mut opened, None)
+                .expect(DISPLAY_ERROR)?
+            {
+                Item::Node(id) => id,
+                Item::More(_) => unreachable!("all the children are printed"),
+            }
+        };
+        // Drop the line break separating the node from the previous one.
+        writer.fmt.clear();
+        write!(writer, "{}", arena[id].get()).expect(DISPLAY_ERROR);
+        if writer.fmt.is_empty() {
+            // Empty data, keep the branch of the node visible.
+            writer.write_indent().expect(DISPLAY_ERROR);
+        }
+        let mut line = mem::take(&mut writer.fmt);
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        Some(line)
+    })
+}
@@ -6,6 +6,8 @@ use core::fmt;
 #[cfg(feature = "std")]
 use std::{error, fmt};
 
+use crate::NodeId;
+
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy)]
 /// Possible node failures.
@@ -19,36 +21,120 @@ pub enum NodeError {
     /// Attempt to insert a node after itself.
     InsertAfterSelf,
     /// Attempt to insert a removed node, or insert to a removed node.
+    ///
+    /// This is no longer returned: the insertion methods report
+    /// [`NodeError::RemovedNode`] instead, which identifies the removed node.
+    /// Code matching on this variant has to match on that one.
+    ///
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    #[deprecated(
+        since = "4.8.0",
+        note = "please, match `NodeError::RemovedNode` instead, which the insertion methods return"
+    )]
     Removed,
+    /// Attempt to insert a removed node, or insert to a removed node.
+    ///
+    /// Carries the ID of the removed node.
+    RemovedNode(NodeId),
     /// Attempt to append an ancestor node to a descendant.
     AppendAncestor,
     /// Attempt to prepend an ancestor node to a descendant.
     PrependAncestor,
+    /// Attempt to reorder children with a list that is not a permutation of
+    /// them.
+    InvalidPermutation,
+    /// Attempt to refer to a parent position that is not before the item.
+    ///
+    /// Carries the position of the offending item.
+    InvalidParentIndex(usize),
+    /// Attempt to insert a node between two nodes that are not adjacent
+    /// siblings.
+    NotAdjacentSiblings,
+    /// Attempt to access a node that is not part of the arena.
+    ///
+    /// Carries the ID of the missing node.
+    NotFound(NodeId),
+    /// Attempt to swap two nodes that do not share a parent.
+    NotSiblings,
 }
 
 impl NodeError {
+    #[allow(deprecated)]
     fn as_str(self) -> &'static str {
         match self {
             NodeError::AppendSelf => "Can not append a node to itself",
             NodeError::PrependSelf => "Can not prepend a node to itself",
             NodeError::InsertBeforeSelf => "Can not insert a node before itself",
             NodeError::InsertAfterSelf => "Can not insert a node after itself",
-            NodeError::Removed => "Removed node cannot have any parent, siblings, and children",
+            NodeError::Removed | NodeError::RemovedNode(_) => {
+                "Removed node cannot have any parent, siblings, and children"
+            }
             NodeError::AppendAncestor => "Can not append a node to its descendant",
             NodeError::PrependAncestor => "Can not prepend a node to its descendant",
+            NodeError::InvalidPermutation => {
+                "Can not reorder children with a list that is not a permutation of them"
+            }
+            NodeError::InvalidParentIndex(_) => "Parent position must precede the item",
+            NodeError::NotAdjacentSiblings => {
+                "Can not insert a node between nodes that are not adjacent siblings"
+            }
+            NodeError::NotFound(_) => "Node is not part of the arena",
+            NodeError::NotSiblings => "Can not swap nodes that do not share a parent",
         }
     }
 }
 
 impl fmt::Display for NodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
+        match self {
+            NodeError::RemovedNode(id) => write!(
+                f,
+                "Removed node {} cannot have any parent, siblings, and children",
+                id
+            ),
+            NodeError::InvalidParentIndex(index) => write!(
+                f,
+                "Parent position of the item at {} must precede the item",
+                index
+            ),
+            NodeError::NotFound(id) => write!(f, "Node {} is not part of the arena", id),
+            _ => f.write_str(self.as_str()),
+        }
     }
 }
 
 #[cfg(feature = "std")]
 impl error::Error for NodeError {}
 
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Possible S-expression parsing failures.
+pub enum ParseError {
+    /// The input does not contain any node.
+    Empty,
+    /// The input ends before all nodes are closed.
+    Unclosed,
+    /// The input contains an unexpected character.
+    ///
+    /// Carries the byte offset of the character.
+    Unexpected(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("S-expression does not contain any node"),
+            ParseError::Unclosed => f.write_str("S-expression ends before all nodes are closed"),
+            ParseError::Unexpected(position) => {
+                write!(f, "Unexpected character at position {}", position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ParseError {}
+
 /// An error type that represents the given structure or argument is
 /// inconsistent or invalid.
 // Intended for internal use.
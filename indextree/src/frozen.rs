@@ -0,0 +1,250 @@
+//! Immutable, pre-order laid out arena.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Arena, NodeId};
+
+/// An immutable snapshot of an [`Arena`], laid out in pre-order.
+///
+/// Removed nodes are dropped, and the live nodes are stored in the order of
+/// [`Arena::iter_tree_order`], so that the descendants of a node are exactly
+/// the `n` nodes following it, where `n` is its precomputed subtree size.
+/// Subtree queries such as [`is_descendant`] are O(1), and traversals are
+/// plain slice iterations.
+///
+/// The nodes keep the IDs they had in the source arena.
+///
+/// Created by [`Arena::freeze`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::iter_tree_order`]: struct.Arena.html#method.iter_tree_order
+/// [`Arena::freeze`]: struct.Arena.html#method.freeze
+/// [`is_descendant`]: #method.is_descendant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenArena<T> {
+    /// IDs of the nodes, in pre-order.
+    ids: Vec<NodeId>,
+    /// Data of the nodes, in pre-order.
+    data: Vec<T>,
+    /// Position of the parent of each node, `None` for roots.
+    parents: Vec<Option<usize>>,
+    /// Number of nodes in the subtree of each node, itself included.
+    sizes: Vec<usize>,
+    /// Positions indexed by the zero-based node index, `None` for removed
+    /// nodes.
+    positions: Vec<Option<usize>>,
+}
+
+impl<T> FrozenArena<T> {
+    /// Freezes the given arena.
+    pub(crate) fn new(arena: Arena<T>) -> Self {
+        let ids: Vec<NodeId> = arena.iter_tree_order().collect();
+        let mut positions = Vec::with_capacity(arena.count());
+        positions.resize(arena.count(), None);
+        for (position, id) in ids.iter().enumerate() {
+            positions[id.index0()] = Some(position);
+        }
+
+        let parents: Vec<Option<usize>> = ids
+            .iter()
+            .map(|&id| {
+                arena[id]
+                    .parent()
+                    .and_then(|parent| positions[parent.index0()])
+            })
+            .collect();
+        let mut sizes = Vec::with_capacity(ids.len());
+        sizes.resize(ids.len(), 1);
+        for position in (0..ids.len()).rev() {
+            if let Some(parent) = parents[position] {
+                sizes[parent] += sizes[position];
+            }
+        }
+
+        let mut slots = arena.into_data();
+        let data = ids
+            .iter()
+            .map(|id| {
+                slots[id.index0()]
+                    .take()
+                    .expect("live nodes should have data")
+            })
+            .collect();
+
+        Self {
+            ids,
+            data,
+            parents,
+            sizes,
+            positions,
+        }
+    }
+
+    /// Returns the position of the given node, if it is part of the arena.
+    fn position(&self, id: NodeId) -> Option<usize> {
+        self.positions
+            .get(id.index0())
+            .copied()
+            .flatten()
+            .filter(|&position| self.ids[position] == id)
+    }
+
+    /// Returns the number of nodes in the arena.
+    pub fn count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the arena contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns a reference to the data of the given node, if it is part of
+    /// the arena.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.position(id).map(|position| &self.data[position])
+    }
+
+    /// Returns the parent of the given node, if any.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.position(id)
+            .and_then(|position| self.parents[position])
+            .map(|parent| self.ids[parent])
+    }
+
+    /// Returns an iterator of the roots of the arena, in tree order.
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.siblings_from(0, self.ids.len())
+    }
+
+    /// Returns an iterator of the children of the given node.
+    ///
+    /// The iterator is empty if the node is not part of the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let frozen = arena.freeze();
+    /// let mut iter = frozen.children(n1);
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let (start, end) = match self.position(id) {
+            Some(position) => (position + 1, position + self.sizes[position]),
+            None => (0, 0),
+        };
+        self.siblings_from(start, end)
+    }
+
+    /// Returns an iterator of the given node and its descendants, in
+    /// pre-order.
+    ///
+    /// The iterator is empty if the node is not part of the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let frozen = arena.freeze();
+    /// let mut iter = frozen.descendants(n1);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_1_1));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn descendants(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let range = match self.position(id) {
+            Some(position) => position..position + self.sizes[position],
+            None => 0..0,
+        };
+        self.ids[range].iter().copied()
+    }
+
+    /// Returns `true` if `node` is `ancestor` itself or one of its
+    /// descendants.
+    ///
+    /// Returns `false` if either node is not part of the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let frozen = arena.freeze();
+    /// assert!(frozen.is_descendant(n1, n1_1_1));
+    /// assert!(frozen.is_descendant(n1_2, n1_2));
+    /// assert!(!frozen.is_descendant(n1_2, n1_1_1));
+    /// assert!(!frozen.is_descendant(n1_1_1, n1));
+    /// ```
+    pub fn is_descendant(&self, ancestor: NodeId, node: NodeId) -> bool {
+        match (self.position(ancestor), self.position(node)) {
+            (Some(ancestor), Some(node)) => {
+                ancestor <= node && node < ancestor + self.sizes[ancestor]
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator of the consecutive subtrees starting at `start`
+    /// and ending before `end`.
+    fn siblings_from(&self, start: usize, end: usize) -> impl Iterator<Item = NodeId> + '_ {
+        let mut position = start;
+        core::iter::from_fn(move || {
+            if position >= end {
+                return None;
+            }
+            let id = self.ids[position];
+            position += self.sizes[position];
+            Some(id)
+        })
+    }
+}
@@ -1,35 +1,101 @@
 //! Node ID.
 
 #[cfg(not(feature = "std"))]
-use core::{fmt, num::NonZeroUsize};
+use alloc::{
+    collections::{BinaryHeap, VecDeque},
+    vec,
+    vec::Vec,
+};
+
+#[cfg(not(feature = "std"))]
+use core::{
+    cmp::{Ordering, Reverse},
+    fmt,
+    hash::{Hash, Hasher},
+    num::NonZeroUsize,
+};
 
 #[cfg(feature = "deser")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "std")]
-use std::{fmt, num::NonZeroUsize};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, VecDeque},
+    fmt,
+    hash::{Hash, Hasher},
+    num::NonZeroUsize,
+    vec,
+};
+
+#[cfg(feature = "retain-removed-data")]
+use crate::node::NodeData;
+
+#[cfg(feature = "std")]
+use crate::debug_pretty_print::pretty_lines;
 
 #[allow(deprecated)]
 use crate::{
-    debug_pretty_print::DebugPrettyPrint,
-    relations::{insert_last_unchecked, insert_with_neighbors},
+    debug_pretty_print::{DebugPrettyPrint, PrettyPrintWith},
+    relations::{connect_neighbors, insert_last_unchecked, insert_with_neighbors},
     siblings_range::SiblingsRange,
-    Ancestors, Arena, Children, Descendants, FollowingSiblings, NodeError, PrecedingSiblings,
-    Predecessors, ReverseChildren, ReverseTraverse, Traverse,
+    summary::ChildHeights,
+    Ancestors, AncestorsWithDepth, Arena, BreadthFirstReverse, Children, Descendants,
+    FollowingSiblings, InOrder, NodeEdge, NodeError, Op, PrecedingSiblings,
+    PrecedingSiblingsInOrder, Predecessors, RemovalEffect, ReverseChildren, ReverseTraverse,
+    Traverse, TreeVisitor, WalkEvent,
 };
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Hash)]
+#[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "deser", derive(Deserialize, Serialize))]
 /// A node identifier within a particular [`Arena`].
 ///
 /// This ID is used to get [`Node`] references from an [`Arena`].
 ///
+/// An ID also carries the generation of the arena it was created in, which
+/// is bumped by [`Arena::clear`] (see [`is_current`]). The generation is
+/// neither compared, ordered nor hashed, and is not serialized: IDs compare
+/// and serialize as they did before generations were tracked, and
+/// deserialized IDs belong to generation 0.
+///
 /// [`Arena`]: struct.Arena.html
+/// [`Arena::clear`]: struct.Arena.html#method.clear
 /// [`Node`]: struct.Node.html
+/// [`is_current`]: struct.NodeId.html#method.is_current
 pub struct NodeId {
     /// One-based index.
     index1: NonZeroUsize,
     stamp: NodeStamp,
+    /// Generation of the arena the node was created in.
+    #[cfg_attr(feature = "deser", serde(default, skip_serializing))]
+    generation: u32,
+}
+
+impl PartialEq for NodeId {
+    fn eq(&self, other: &Self) -> bool {
+        self.index1 == other.index1 && self.stamp == other.stamp
+    }
+}
+
+impl Eq for NodeId {}
+
+impl PartialOrd for NodeId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NodeId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index1, self.stamp).cmp(&(other.index1, other.stamp))
+    }
+}
+
+impl Hash for NodeId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index1.hash(state);
+        self.stamp.hash(state);
+    }
 }
 
 /// A stamp for node reuse, use to detect if the node of a `NodeId` point to
@@ -62,6 +128,15 @@ impl NodeStamp {
         self.0 = -self.0;
         *self
     }
+
+    /// Returns the stamp the slot is reused with next.
+    pub fn next(self) -> Self {
+        if self.is_removed() {
+            Self(self.0.saturating_neg())
+        } else {
+            Self(self.0.saturating_add(1))
+        }
+    }
 }
 
 impl fmt::Display for NodeId {
@@ -91,8 +166,16 @@ impl NodeId {
     }
 
     /// Creates a new `NodeId` from the given one-based index.
-    pub(crate) fn from_non_zero_usize(index1: NonZeroUsize, stamp: NodeStamp) -> Self {
-        NodeId { index1, stamp }
+    pub(crate) fn from_non_zero_usize(
+        index1: NonZeroUsize,
+        stamp: NodeStamp,
+        generation: u32,
+    ) -> Self {
+        NodeId {
+            index1,
+            stamp,
+            generation,
+        }
     }
 
     /// Return if the `Node` of NodeId point to is removed.
@@ -100,6 +183,152 @@ impl NodeId {
         arena[self].stamp != self.stamp
     }
 
+    /// Returns the data the node had when it was removed, as long as its
+    /// storage slot has not been reused by a new node.
+    ///
+    /// Returns `None` if the node is not removed, or if its data was moved
+    /// out of the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert_eq!(n1.removed_data(&arena), None);
+    ///
+    /// n1.remove(&mut arena);
+    /// assert_eq!(n1.removed_data(&arena), Some(&"1"));
+    ///
+    /// // The slot is reused by the new node.
+    /// arena.new_node("2");
+    /// assert_eq!(n1.removed_data(&arena), None);
+    /// ```
+    #[cfg(feature = "retain-removed-data")]
+    pub fn removed_data<T>(self, arena: &Arena<T>) -> Option<&T> {
+        let node = arena.get(self)?;
+        let mut removed = self.stamp;
+        removed.as_removed();
+        if !self.is_current(arena) || node.stamp != removed {
+            return None;
+        }
+        match &node.data {
+            NodeData::Removed(_, data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the node was created in the current generation of the
+    /// arena, i.e. after the last [`clear()`].
+    ///
+    /// This can be used to detect stale ids that were handed out before the
+    /// arena was cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let before = arena.new_node("before");
+    /// assert!(before.is_current(&arena));
+    ///
+    /// arena.clear();
+    /// let after = arena.new_node("after");
+    /// assert!(!before.is_current(&arena));
+    /// assert!(after.is_current(&arena));
+    /// ```
+    ///
+    /// [`clear()`]: struct.Arena.html#method.clear
+    pub fn is_current<T>(self, arena: &Arena<T>) -> bool {
+        self.generation == arena.generation()
+    }
+
+    /// Marks this node as dirty, e.g. to flag it for reprocessing.
+    ///
+    /// [`Arena::set_data`] marks the node it replaces the data of as well,
+    /// whereas mere mutable borrows such as [`Arena::get_mut`] do not. The
+    /// flags are kept until [`Arena::clear_dirty`] is called or the node is
+    /// removed. They are neither serialized nor compared for equality.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    ///
+    /// n1_1.mark_dirty(&mut arena);
+    /// assert!(n1_1.is_dirty(&arena));
+    /// assert!(!n1.is_dirty(&arena));
+    /// ```
+    ///
+    /// [`Arena::get_mut`]: struct.Arena.html#method.get_mut
+    /// [`Arena::set_data`]: struct.Arena.html#method.set_data
+    /// [`Arena::clear_dirty`]: struct.Arena.html#method.clear_dirty
+    pub fn mark_dirty<T>(self, arena: &mut Arena<T>) {
+        assert!(!self.is_removed(arena), "the node should not be removed");
+        arena.set_dirty(self);
+    }
+
+    /// Returns `true` if this node is marked as dirty.
+    ///
+    /// Removed nodes are never dirty.
+    ///
+    /// See [`mark_dirty`](#method.mark_dirty).
+    pub fn is_dirty<T>(self, arena: &Arena<T>) -> bool {
+        !self.is_removed(arena) && arena.contains_dirty(self)
+    }
+
+    /// Checks that this ID refers to a live node of the arena, panicking with
+    /// a descriptive message otherwise.
+    ///
+    /// This is a no-op in release builds, and is meant to be used while
+    /// debugging stale IDs.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the ID is out of range of the arena, or if
+    /// the node it referred to was removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let foo = arena.new_node("foo");
+    /// foo.debug_assert_valid(&arena);
+    /// ```
+    ///
+    /// ```should_panic
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let foo = arena.new_node("foo");
+    /// foo.remove(&mut arena);
+    /// # if !cfg!(debug_assertions) { panic!() }
+    /// foo.debug_assert_valid(&arena);
+    /// ```
+    pub fn debug_assert_valid<T>(self, arena: &Arena<T>) {
+        if cfg!(debug_assertions) {
+            match arena.get(self) {
+                None => panic!(
+                    "NodeId {} is out of range of the arena ({} nodes)",
+                    self,
+                    arena.count()
+                ),
+                Some(node) if node.stamp != self.stamp => panic!(
+                    "NodeId {} stamp {} does not match arena (live stamp {})",
+                    self, self.stamp.0, node.stamp.0
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
     /// Returns an iterator of IDs of this node and its ancestors.
     ///
     /// Use [`.skip(1)`][`skip`] or call `.next()` once on the iterator to skip
@@ -142,6 +371,180 @@ impl NodeId {
         Ancestors::new(arena, self)
     }
 
+    /// Returns an iterator of IDs of this node and its ancestors, together
+    /// with their depth.
+    ///
+    /// The node itself is yielded with its absolute depth, and each ancestor
+    /// with a depth decreased by one, down to 0 at the root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1                                                // #3
+    /// //     `-- 1_1                                          // #2
+    /// //         `-- 1_1_1 *                                  // #1
+    ///
+    /// let mut iter = n1_1_1.ancestors_with_depth(&arena);
+    /// assert_eq!(iter.next(), Some((n1_1_1, 2)));             // #1
+    /// assert_eq!(iter.next(), Some((n1_1, 1)));               // #2
+    /// assert_eq!(iter.next(), Some((n1, 0)));                 // #3
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn ancestors_with_depth<T>(self, arena: &Arena<T>) -> AncestorsWithDepth<'_, T> {
+        AncestorsWithDepth::new(arena, self)
+    }
+
+    /// Returns the depth of this node, where the root of its tree has depth
+    /// 0.
+    ///
+    /// This walks the ancestors of the node, unless the depth cache of the
+    /// arena is enabled with [`Arena::enable_depth_cache`], in which case it
+    /// is O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    ///
+    /// assert_eq!(n1.depth(&arena), 0);
+    /// assert_eq!(n1_1_1.depth(&arena), 2);
+    /// ```
+    ///
+    /// [`Arena::enable_depth_cache`]: struct.Arena.html#method.enable_depth_cache
+    pub fn depth<T>(self, arena: &Arena<T>) -> usize {
+        arena
+            .cached_depth(self)
+            .unwrap_or_else(|| self.ancestors(arena).count() - 1)
+    }
+
+    /// Returns the ID of the ancestor of this node at the given absolute
+    /// depth, where the root has depth 0.
+    ///
+    /// Returns the node itself if `depth` is its own depth, and `None` if the
+    /// node is shallower than `depth`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     `-- 1_1
+    /// //         `-- 1_1_1 *
+    ///
+    /// assert_eq!(n1_1_1.ancestor_at_depth(&arena, 0), Some(n1));
+    /// assert_eq!(n1_1_1.ancestor_at_depth(&arena, 1), Some(n1_1));
+    /// assert_eq!(n1_1_1.ancestor_at_depth(&arena, 2), Some(n1_1_1));
+    /// assert_eq!(n1_1_1.ancestor_at_depth(&arena, 3), None);
+    /// ```
+    pub fn ancestor_at_depth<T>(self, arena: &Arena<T>, depth: usize) -> Option<NodeId> {
+        let own_depth = self.ancestors(arena).count() - 1;
+        let distance = own_depth.checked_sub(depth)?;
+        self.ancestors(arena).nth(distance)
+    }
+
+    /// Returns the IDs of the nodes on the path from this node to `other`.
+    ///
+    /// The path goes up from this node to the lowest common ancestor of both
+    /// nodes, then down to `other`. Both ends are included, and the path of a
+    /// node to itself is that single node.
+    ///
+    /// Returns `None` if the nodes are in different trees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n2 = arena.new_node("2");
+    /// #
+    /// // arena
+    /// // |-- 1
+    /// // |   |-- 1_1
+    /// // |   |   `-- 1_1_1
+    /// // |   `-- 1_2
+    /// // `-- 2
+    ///
+    /// assert_eq!(
+    ///     n1_1_1.path_between(n1_2, &arena),
+    ///     Some(vec![n1_1_1, n1_1, n1, n1_2])
+    /// );
+    /// assert_eq!(n1.path_between(n1_1_1, &arena), Some(vec![n1, n1_1, n1_1_1]));
+    /// assert_eq!(n1_2.path_between(n2, &arena), None);
+    /// ```
+    pub fn path_between<T>(self, other: NodeId, arena: &Arena<T>) -> Option<Vec<NodeId>> {
+        let mut up: Vec<_> = self.ancestors(arena).collect();
+        let mut down: Vec<_> = other.ancestors(arena).collect();
+        if up.last() != down.last() {
+            return None;
+        }
+
+        // Drop the common ancestors above the lowest one.
+        while up.len() >= 2 && down.len() >= 2 && up[up.len() - 2] == down[down.len() - 2] {
+            up.pop();
+            down.pop();
+        }
+        down.pop();
+        up.extend(down.into_iter().rev());
+        Some(up)
+    }
+
+    /// Returns whether this node and `other` belong to the same tree, i.e.
+    /// have the same root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n2 = arena.new_node("2");
+    /// #
+    /// // arena
+    /// // |-- 1
+    /// // |   |-- 1_1
+    /// // |   `-- 1_2
+    /// // `-- 2
+    ///
+    /// assert!(n1_1.same_tree(n1_2, &arena));
+    /// assert!(n1_1.same_tree(n1, &arena));
+    /// assert!(!n1_1.same_tree(n2, &arena));
+    /// ```
+    pub fn same_tree<T>(self, other: NodeId, arena: &Arena<T>) -> bool {
+        self.ancestors(arena).last() == other.ancestors(arena).last()
+    }
+
     /// Returns an iterator of IDs of this node and its predecessors.
     ///
     /// Use [`.skip(1)`][`skip`] or call `.next()` once on the iterator to skip
@@ -256,6 +659,48 @@ impl NodeId {
         PrecedingSiblings::new(arena, self)
     }
 
+    /// Returns an iterator of IDs of the siblings before this node, in
+    /// insertion order.
+    ///
+    /// Unlike [`preceding_siblings`], this starts from the first sibling and
+    /// excludes the node itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1                                          // #1
+    /// //     |   `-- 1_1_1
+    /// //     |-- 1_2                                          // #2
+    /// //     `-- 1_3 *
+    ///
+    /// let mut iter = n1_3.preceding_siblings_in_order(&arena);
+    /// assert_eq!(iter.next(), Some(n1_1));                    // #1
+    /// assert_eq!(iter.next(), Some(n1_2));                    // #2
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`preceding_siblings`]: struct.NodeId.html#method.preceding_siblings
+    pub fn preceding_siblings_in_order<T>(
+        self,
+        arena: &Arena<T>,
+    ) -> PrecedingSiblingsInOrder<'_, T> {
+        PrecedingSiblingsInOrder::new(arena, self)
+    }
+
     /// Returns an iterator of IDs of this node and the siblings after
     /// it.
     ///
@@ -329,8 +774,32 @@ impl NodeId {
         Children::new(arena, self)
     }
 
-    /// Returns an iterator of IDs of this node’s children, in reverse
-    /// order.
+    /// Returns an iterator of the data of the children of this node, in
+    /// insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// n1_1.append_value("1_1_1", &mut arena);
+    /// n1.append_value("1_2", &mut arena);
+    ///
+    /// let mut iter = n1.children_data(&arena);
+    /// assert_eq!(iter.next(), Some(&"1_1"));
+    /// assert_eq!(iter.next(), Some(&"1_2"));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn children_data<T>(self, arena: &Arena<T>) -> impl DoubleEndedIterator<Item = &T> {
+        self.children(arena).map(move |child| arena[child].get())
+    }
+
+    /// Returns the IDs of this node’s children, in order, as an owned vector.
+    ///
+    /// Unlike [`children`], the result does not borrow the arena, so it can
+    /// be used to modify the arena while visiting the children.
     ///
     /// # Examples
     ///
@@ -340,31 +809,100 @@ impl NodeId {
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
     /// # n1.append(n1_1, &mut arena);
-    /// # let n1_1_1 = arena.new_node("1_1_1");
-    /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
     /// #
     /// // arena
     /// // `-- 1
-    /// //     |-- 1_1                                          // #3
-    /// //     |   `-- 1_1_1
-    /// //     |-- 1_2                                          // #2
-    /// //     `-- 1_3                                          // #1
+    /// //     |-- 1_1
+    /// //     `-- 1_2
     ///
-    /// let mut iter = n1.reverse_children(&arena);
-    /// assert_eq!(iter.next(), Some(n1_3));                    // #1
-    /// assert_eq!(iter.next(), Some(n1_2));                    // #2
-    /// assert_eq!(iter.next(), Some(n1_1));                    // #3
-    /// assert_eq!(iter.next(), None);
+    /// for child in n1.children_vec(&arena) {
+    ///     child.append_value("leaf", &mut arena);
+    /// }
+    /// assert_eq!(n1.descendants(&arena).count(), 5);
     /// ```
-    #[allow(deprecated)]
-    #[deprecated(
-        since = "4.7.0",
-        note = "please, use `NodeId::children().rev()` instead if you want to iterate in reverse"
-    )]
+    ///
+    /// [`children`]: struct.NodeId.html#method.children
+    pub fn children_vec<T>(self, arena: &Arena<T>) -> Vec<NodeId> {
+        self.children(arena).collect()
+    }
+
+    /// Replaces the contents of `buf` with the IDs of this node’s children, in
+    /// order.
+    ///
+    /// This is an allocation-free alternative to [`children_vec`] when the
+    /// buffer is reused across calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let mut buf = Vec::new();
+    /// n1.collect_children_into(&arena, &mut buf);
+    /// assert_eq!(buf, &[n1_1, n1_2]);
+    ///
+    /// n1_1.collect_children_into(&arena, &mut buf);
+    /// assert_eq!(buf, &[n1_1_1]);
+    /// ```
+    ///
+    /// [`children_vec`]: struct.NodeId.html#method.children_vec
+    pub fn collect_children_into<T>(self, arena: &Arena<T>, buf: &mut Vec<NodeId>) {
+        buf.clear();
+        buf.extend(self.children(arena));
+    }
+
+    /// Returns an iterator of IDs of this node’s children, in reverse
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1                                          // #3
+    /// //     |   `-- 1_1_1
+    /// //     |-- 1_2                                          // #2
+    /// //     `-- 1_3                                          // #1
+    ///
+    /// let mut iter = n1.reverse_children(&arena);
+    /// assert_eq!(iter.next(), Some(n1_3));                    // #1
+    /// assert_eq!(iter.next(), Some(n1_2));                    // #2
+    /// assert_eq!(iter.next(), Some(n1_1));                    // #3
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[allow(deprecated)]
+    #[deprecated(
+        since = "4.7.0",
+        note = "please, use `NodeId::children().rev()` instead if you want to iterate in reverse"
+    )]
     pub fn reverse_children<T>(self, arena: &Arena<T>) -> ReverseChildren<'_, T> {
         ReverseChildren::new(arena, self)
     }
@@ -417,15 +955,59 @@ impl NodeId {
         Descendants::new(arena, self)
     }
 
-    /// An iterator of the "sides" of a node visited during a depth-first pre-order traversal,
-    /// where node sides are visited start to end and children are visited in insertion order.
+    /// Returns an iterator of mutable references to the data of this node and
+    /// its descendants, in pre-order depth-first search (as [`descendants`]).
     ///
-    /// i.e. node.start -> first child -> second child -> node.end
+    /// The references are handed out lazily, one per step, without collecting
+    /// the subtree up front.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use indextree::{Arena, NodeEdge};
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node(1);
+    /// # let n1_1 = arena.new_node(2);
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node(3);
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node(4);
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 2
+    /// //     |   `-- 3
+    /// //     `-- 4
+    ///
+    /// let mut sum = 0;
+    /// for data in n1.descendants_data_mut(&mut arena) {
+    ///     sum += *data;
+    ///     *data = sum;
+    /// }
+    /// assert_eq!(*arena[n1].get(), 1);
+    /// assert_eq!(*arena[n1_1].get(), 3);
+    /// assert_eq!(*arena[n1_1_1].get(), 6);
+    /// assert_eq!(*arena[n1_2].get(), 10);
+    /// ```
+    ///
+    /// [`descendants`]: struct.NodeId.html#method.descendants
+    pub fn descendants_data_mut<T>(self, arena: &mut Arena<T>) -> impl Iterator<Item = &mut T> {
+        arena.subtree_data_mut(self)
+    }
+
+    /// Returns an iterator of IDs of this node and its descendants, in tree
+    /// order, collected up front.
+    ///
+    /// Unlike [`descendants`], the returned iterator does not borrow the
+    /// arena, so the arena can be modified during the iteration (e.g. by
+    /// removing the visited nodes). The iterator still yields every node that
+    /// was a descendant when it was created, including removed ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
     /// # let mut arena = Arena::new();
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
@@ -434,42 +1016,42 @@ impl NodeId {
     /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
     /// #
     /// // arena
-    /// // `-- 1                                                // #1, #10
-    /// //     |-- 1_1                                          // #2, #5
-    /// //     |   `-- 1_1_1                                    // #3, #4
-    /// //     |-- 1_2                                          // #6, #7
-    /// //     `-- 1_3                                          // #8, #9
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
     ///
-    /// let mut iter = n1.traverse(&arena);
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1)));     // #1
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1)));   // #2
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1_1))); // #3
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1_1)));   // #4
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1)));     // #5
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_2)));   // #6
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_2)));     // #7
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_3)));   // #8
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_3)));     // #9
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1)));       // #10
+    /// for id in n1_1.descendants_snapshot(&arena) {
+    ///     id.remove(&mut arena);
+    /// }
+    /// assert!(n1_1.is_removed(&arena));
+    /// assert!(n1_1_1.is_removed(&arena));
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_2));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn traverse<T>(self, arena: &Arena<T>) -> Traverse<'_, T> {
-        Traverse::new(arena, self)
+    ///
+    /// [`descendants`]: struct.NodeId.html#method.descendants
+    pub fn descendants_snapshot<T>(self, arena: &Arena<T>) -> vec::IntoIter<NodeId> {
+        self.descendants(arena).collect::<Vec<_>>().into_iter()
     }
 
-    /// An iterator of the "sides" of a node visited during a depth-first pre-order traversal,
-    /// where nodes are visited end to start and children are visited in reverse insertion order.
+    /// Returns an iterator of IDs of this node and its descendants, in
+    /// pre-order (as [`descendants`]), each paired with the size of its
+    /// sibling group.
     ///
-    /// i.e. node.end -> second child -> first child -> node.start
+    /// The size of the sibling group of a node is the number of children of
+    /// its parent, i.e. the node itself and all of its siblings. The number
+    /// of children of each node is only counted once.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use indextree::{Arena, NodeEdge};
+    /// # use indextree::Arena;
     /// # let mut arena = Arena::new();
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
@@ -482,60 +1064,111 @@ impl NodeId {
     /// # n1.append(n1_3, &mut arena);
     /// #
     /// // arena
-    /// // `-- 1                                                // #1, #10
-    /// //     |-- 1_1                                          // #6, #9
-    /// //     |   `-- 1_1_1                                    // #7, #8
-    /// //     |-- 1_2                                          // #4, #5
-    /// //     `-- 1_3                                          // #2, #3
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     |-- 1_2
+    /// //     `-- 1_3
     ///
-    /// let mut iter = n1.reverse_traverse(&arena);
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1)));       // #1
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_3)));     // #2
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_3)));   // #3
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_2)));     // #4
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_2)));   // #5
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1)));     // #6
-    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1_1)));   // #7
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1_1))); // #8
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1)));   // #9
-    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1)));     // #10
+    /// let mut iter = n1.descendants_with_sibling_count(&arena);
+    /// assert_eq!(iter.next(), Some((n1, 1)));
+    /// assert_eq!(iter.next(), Some((n1_1, 3)));
+    /// assert_eq!(iter.next(), Some((n1_1_1, 1)));
+    /// assert_eq!(iter.next(), Some((n1_2, 3)));
+    /// assert_eq!(iter.next(), Some((n1_3, 3)));
     /// assert_eq!(iter.next(), None);
     /// ```
     ///
+    /// [`descendants`]: struct.NodeId.html#method.descendants
+    pub fn descendants_with_sibling_count<T>(
+        self,
+        arena: &Arena<T>,
+    ) -> impl Iterator<Item = (NodeId, usize)> + '_ {
+        // Both iterators include `self`.
+        let group =
+            self.preceding_siblings(arena).count() + self.following_siblings(arena).count() - 1;
+        // Numbers of children of the nodes being visited.
+        let mut counts: Vec<usize> = Vec::new();
+        self.traverse(arena).filter_map(move |edge| match edge {
+            NodeEdge::Start(id) => {
+                let count = counts.last().copied().unwrap_or(group);
+                counts.push(id.children(arena).count());
+                Some((id, count))
+            }
+            NodeEdge::End(_) => {
+                counts.pop();
+                None
+            }
+        })
+    }
+
+    /// Returns an iterator of IDs of this node and its descendants, in order of
+    /// increasing cumulative cost.
+    ///
+    /// The cumulative cost of a node is the sum of `cost` over the path from
+    /// this node down to it, both included. Nodes of equal cumulative cost are
+    /// yielded in the order they were discovered. This is a best-first search
+    /// using a binary heap as frontier.
+    ///
+    /// # Examples
+    ///
     /// ```
-    /// # use indextree::{Arena, NodeEdge};
+    /// # use indextree::Arena;
     /// # let mut arena = Arena::new();
-    /// # let n1 = arena.new_node("1");
-    /// # let n1_1 = arena.new_node("1_1");
+    /// # let n1 = arena.new_node(0);
+    /// # let n1_1 = arena.new_node(1);
     /// # n1.append(n1_1, &mut arena);
-    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # let n1_1_1 = arena.new_node(1);
     /// # n1_1.append(n1_1_1, &mut arena);
-    /// # let n1_2 = arena.new_node("1_2");
+    /// # let n1_2 = arena.new_node(5);
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
     /// #
     /// // arena
-    /// // `-- 1                                                // #1, #10
-    /// //     |-- 1_1                                          // #6, #9
-    /// //     |   `-- 1_1_1                                    // #7, #8
-    /// //     |-- 1_2                                          // #4, #5
-    /// //     `-- 1_3                                          // #2, #3
-    /// let traverse = n1.traverse(&arena).collect::<Vec<_>>();
-    /// let mut reverse = n1.reverse_traverse(&arena).collect::<Vec<_>>();
-    /// reverse.reverse();
-    /// assert_eq!(traverse, reverse);
+    /// // `-- 0                                                // #1
+    /// //     |-- 1                                            // #2
+    /// //     |   `-- 1                                        // #3
+    /// //     `-- 5                                            // #4
+    ///
+    /// let mut iter = n1.descendants_by_cost(&arena, |&cost| cost);
+    /// assert_eq!(iter.next(), Some(n1));                      // #1
+    /// assert_eq!(iter.next(), Some(n1_1));                    // #2
+    /// assert_eq!(iter.next(), Some(n1_1_1));                  // #3
+    /// assert_eq!(iter.next(), Some(n1_2));                    // #4
+    /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn reverse_traverse<T>(self, arena: &Arena<T>) -> ReverseTraverse<'_, T> {
-        ReverseTraverse::new(arena, self)
+    pub fn descendants_by_cost<'a, T, F>(
+        self,
+        arena: &'a Arena<T>,
+        cost: F,
+    ) -> impl Iterator<Item = NodeId> + 'a
+    where
+        F: Fn(&T) -> u64 + 'a,
+    {
+        // Ties are broken by the discovery order.
+        let mut discovered = 0_usize;
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Reverse((cost(arena[self].get()), discovered, self)));
+        core::iter::from_fn(move || {
+            let Reverse((total, _, id)) = frontier.pop()?;
+            for child in id.children(arena) {
+                discovered += 1;
+                let child_total = total.saturating_add(cost(arena[child].get()));
+                frontier.push(Reverse((child_total, discovered, child)));
+            }
+            Some(id)
+        })
     }
 
-    /// Detaches a node from its parent and siblings. Children are not affected.
+    /// Returns an iterator of IDs of this node and its descendants, in
+    /// breadth-first order where the children of each node are visited in
+    /// reverse insertion order.
+    ///
+    /// Levels are still visited from top to bottom.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use indextree::{Arena, NodeEdge};
+    /// # use indextree::Arena;
     /// # let mut arena = Arena::new();
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
@@ -544,313 +1177,2210 @@ impl NodeId {
     /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
     /// #
     /// // arena
-    /// // `-- (implicit)
-    /// //     `-- 1
-    /// //         |-- 1_1
-    /// //         |   `-- 1_1_1
-    /// //         |-- 1_2 *
-    /// //         `-- 1_3
-    ///
-    /// n1_2.detach(&mut arena);
-    /// // arena
-    /// // |-- (implicit)
-    /// // |   `-- 1
-    /// // |       |-- 1_1
-    /// // |       |   `-- 1_1_1
-    /// // |       `-- 1_3
-    /// // `-- (implicit)
-    /// //     `-- 1_2 *
-    ///
-    /// assert!(arena[n1_2].parent().is_none());
-    /// assert!(arena[n1_2].previous_sibling().is_none());
-    /// assert!(arena[n1_2].next_sibling().is_none());
+    /// // `-- 1                                                // #1
+    /// //     |-- 1_1                                          // #3
+    /// //     |   `-- 1_1_1                                    // #5
+    /// //     `-- 1_2                                          // #2
+    /// //         `-- 1_2_1                                    // #4
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_1_1));
-    /// assert_eq!(iter.next(), Some(n1_3));
+    /// let mut iter = n1.breadth_first_reverse(&arena);
+    /// assert_eq!(iter.next(), Some(n1));                      // #1
+    /// assert_eq!(iter.next(), Some(n1_2));                    // #2
+    /// assert_eq!(iter.next(), Some(n1_1));                    // #3
+    /// assert_eq!(iter.next(), Some(n1_2_1));                  // #4
+    /// assert_eq!(iter.next(), Some(n1_1_1));                  // #5
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn detach<T>(self, arena: &mut Arena<T>) {
-        let range = SiblingsRange::new(self, self).detach_from_siblings(arena);
-        range
-            .rewrite_parents(arena, None)
-            .expect("Should never happen: `None` as parent is always valid");
-
-        // Ensure the node is surely detached.
-        debug_assert!(
-            arena[self].is_detached(),
-            "The node should be successfully detached"
-        );
+    pub fn breadth_first_reverse<T>(self, arena: &Arena<T>) -> BreadthFirstReverse<'_, T> {
+        BreadthFirstReverse::new(arena, self)
     }
 
-    /// Appends a new child to this node, after existing children.
-    ///
-    /// # Panics
-    ///
-    /// Panics if:
-    ///
-    /// * the given new child is `self`, or
-    /// * the given new child is an ancestor of `self`, or
-    /// * the current node or the given new child was already [`remove`]d.
+    /// Returns an iterator of IDs of this node and its descendants, as an
+    /// in-order traversal of a binary tree.
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// This assumes the tree is used as a binary tree: the first child of a
+    /// node is its left child, and the second child is its right child. If a
+    /// node has more than two children, only the first two are considered
+    /// and the others (with their descendants) are skipped. A node with a
+    /// single child is considered to have a left child only.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// let n1_1 = arena.new_node("1_1");
-    /// n1.append(n1_1, &mut arena);
-    /// let n1_2 = arena.new_node("1_2");
-    /// n1.append(n1_2, &mut arena);
-    /// let n1_3 = arena.new_node("1_3");
-    /// n1.append(n1_3, &mut arena);
-    ///
+    /// # let mut arena = Arena::new();
+    /// # let n4 = arena.new_node(4);
+    /// # let n2 = n4.append_value(2, &mut arena);
+    /// # let n6 = n4.append_value(6, &mut arena);
+    /// # let n1 = n2.append_value(1, &mut arena);
+    /// # let n3 = n2.append_value(3, &mut arena);
+    /// # let n5 = n6.append_value(5, &mut arena);
+    /// #
     /// // arena
-    /// // `-- 1
-    /// //     |-- 1_1
-    /// //     |-- 1_2
-    /// //     `-- 1_3
-    ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_2));
-    /// assert_eq!(iter.next(), Some(n1_3));
+    /// // `-- 4                                                // #4
+    /// //     |-- 2                                            // #2
+    /// //     |   |-- 1                                        // #1
+    /// //     |   `-- 3                                        // #3
+    /// //     `-- 6                                            // #6
+    /// //         `-- 5                                        // #5
+    ///
+    /// let mut iter = n4.in_order(&arena);
+    /// assert_eq!(iter.next(), Some(n1));                      // #1
+    /// assert_eq!(iter.next(), Some(n2));                      // #2
+    /// assert_eq!(iter.next(), Some(n3));                      // #3
+    /// assert_eq!(iter.next(), Some(n4));                      // #4
+    /// assert_eq!(iter.next(), Some(n5));                      // #5
+    /// assert_eq!(iter.next(), Some(n6));                      // #6
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn in_order<T>(self, arena: &Arena<T>) -> InOrder<'_, T> {
+        InOrder::new(arena, self)
+    }
+
+    /// Returns an iterator of IDs of the descendants of this node at the given
+    /// depth relative to this node, in document order.
+    ///
+    /// Depth 0 is this node itself. Subtrees deeper than `depth` are not
+    /// visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    /// //         `-- 1_2_1
+    ///
+    /// let mut iter = n1.nodes_at_depth(&arena, 1);
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    ///
+    /// let mut iter = n1.nodes_at_depth(&arena, 2);
+    /// assert_eq!(iter.next(), Some(n1_1_1));
+    /// assert_eq!(iter.next(), Some(n1_2_1));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn nodes_at_depth<T>(
+        self,
+        arena: &Arena<T>,
+        depth: usize,
+    ) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = Vec::new();
+        stack.push((self, 0));
+        core::iter::from_fn(move || {
+            while let Some((id, current)) = stack.pop() {
+                if current == depth {
+                    return Some(id);
+                }
+                stack.extend(id.children(arena).rev().map(|child| (child, current + 1)));
+            }
+            None
+        })
+    }
+
+    /// Returns the IDs of this node and its descendants grouped by their depth
+    /// relative to this node.
+    ///
+    /// The outer index is the depth, so the first level only contains this
+    /// node. Nodes within a level are in breadth-first order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// assert_eq!(
+    ///     n1.levels(&arena),
+    ///     vec![vec![n1], vec![n1_1, n1_2], vec![n1_1_1]]
+    /// );
+    /// ```
+    pub fn levels<T>(self, arena: &Arena<T>) -> Vec<Vec<NodeId>> {
+        let mut levels = Vec::new();
+        let mut level = Vec::new();
+        level.push(self);
+        while !level.is_empty() {
+            let next = level.iter().flat_map(|id| id.children(arena)).collect();
+            levels.push(core::mem::replace(&mut level, next));
+        }
+        levels
+    }
+
+    /// Returns the maximum number of nodes on a single depth level of the
+    /// subtree of this node.
+    ///
+    /// A node without children has a width of 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_1_2 = arena.new_node("1_1_2");
+    /// # n1_1.append(n1_1_2, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   |-- 1_1_1
+    /// //     |   `-- 1_1_2
+    /// //     `-- 1_2
+    /// //         `-- 1_2_1
+    ///
+    /// assert_eq!(n1.max_width(&arena), 3);
+    /// assert_eq!(n1_1.max_width(&arena), 2);
+    /// assert_eq!(n1_2_1.max_width(&arena), 1);
+    /// ```
+    pub fn max_width<T>(self, arena: &Arena<T>) -> usize {
+        let mut queue = VecDeque::new();
+        queue.push_back(self);
+        let mut width = 0;
+        while !queue.is_empty() {
+            width = width.max(queue.len());
+            for _ in 0..queue.len() {
+                let id = queue
+                    .pop_front()
+                    .expect("the level should not be exhausted");
+                queue.extend(id.children(arena));
+            }
+        }
+        width
+    }
+
+    /// Returns an iterator of the runs of consecutive leaves among this node
+    /// and its descendants, in pre-order.
+    ///
+    /// A run ends whenever a node with children is encountered, so each run
+    /// only contains leaves that are adjacent in pre-order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1                                          // #1
+    /// //     |-- 1_2
+    /// //     |   `-- 1_2_1                                    // #2
+    /// //     `-- 1_3                                          // #2
+    ///
+    /// let mut iter = n1.leaf_runs(&arena);
+    /// assert_eq!(iter.next(), Some(vec![n1_1]));              // #1
+    /// assert_eq!(iter.next(), Some(vec![n1_2_1, n1_3]));      // #2
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn leaf_runs<T>(self, arena: &Arena<T>) -> impl Iterator<Item = Vec<NodeId>> + '_ {
+        let mut descendants = self.descendants(arena);
+        core::iter::from_fn(move || {
+            let mut run = Vec::new();
+            for id in descendants.by_ref() {
+                if arena[id].first_child.is_none() {
+                    run.push(id);
+                } else if !run.is_empty() {
+                    return Some(run);
+                }
+            }
+            (!run.is_empty()).then_some(run)
+        })
+    }
+
+    /// Returns an iterator of the frontier of the expanded region rooted at
+    /// this node: the descendants that are not `expanded` while their parent
+    /// is, in pre-order.
+    ///
+    /// The traversal does not go below the nodes of the frontier, so
+    /// `expanded` is only called on this node, the expanded descendants and
+    /// the frontier itself. The iterator is empty if this node is not
+    /// expanded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1 (expanded)
+    /// //     |-- 1_1 (expanded)
+    /// //     |   `-- 1_1_1 *
+    /// //     `-- 1_2 *
+    /// //         `-- 1_2_1
+    ///
+    /// let mut iter = n1.frontier(&arena, |id, _| id == n1 || id == n1_1);
+    /// assert_eq!(iter.next(), Some(n1_1_1));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn frontier<'a, T, F>(
+        self,
+        arena: &'a Arena<T>,
+        mut expanded: F,
+    ) -> impl Iterator<Item = NodeId> + 'a
+    where
+        F: FnMut(NodeId, &T) -> bool + 'a,
+    {
+        let mut stack = Vec::new();
+        if expanded(self, arena[self].get()) {
+            stack.extend(self.children(arena).rev());
+        }
+        core::iter::from_fn(move || {
+            while let Some(id) = stack.pop() {
+                if !expanded(id, arena[id].get()) {
+                    return Some(id);
+                }
+                stack.extend(id.children(arena).rev());
+            }
+            None
+        })
+    }
+
+    /// An iterator of the "sides" of a node visited during a depth-first pre-order traversal,
+    /// where node sides are visited start to end and children are visited in insertion order.
+    ///
+    /// i.e. node.start -> first child -> second child -> node.end
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeEdge};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1                                                // #1, #10
+    /// //     |-- 1_1                                          // #2, #5
+    /// //     |   `-- 1_1_1                                    // #3, #4
+    /// //     |-- 1_2                                          // #6, #7
+    /// //     `-- 1_3                                          // #8, #9
+    ///
+    /// let mut iter = n1.traverse(&arena);
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1)));     // #1
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1)));   // #2
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1_1))); // #3
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1_1)));   // #4
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1)));     // #5
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_2)));   // #6
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_2)));     // #7
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_3)));   // #8
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_3)));     // #9
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1)));       // #10
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn traverse<T>(self, arena: &Arena<T>) -> Traverse<'_, T> {
+        Traverse::new(arena, self)
+    }
+
+    /// Returns an iterator of the navigation steps of a depth-first
+    /// pre-order walk of this node and its descendants.
+    ///
+    /// Unlike [`traverse`], which reports the start and the end of every
+    /// node, this reports how the walk moves: [`Down`] to the first child
+    /// (or to this node at the very beginning), [`Next`] to the next
+    /// sibling, or [`Up`] back to the parent once its descendants are done.
+    /// Leaves therefore produce a single event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, WalkEvent};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let mut iter = n1.walk_events(&arena);
+    /// assert_eq!(iter.next(), Some(WalkEvent::Down(n1)));
+    /// assert_eq!(iter.next(), Some(WalkEvent::Down(n1_1)));
+    /// assert_eq!(iter.next(), Some(WalkEvent::Down(n1_1_1)));
+    /// assert_eq!(iter.next(), Some(WalkEvent::Up(n1_1)));
+    /// assert_eq!(iter.next(), Some(WalkEvent::Next(n1_2)));
+    /// assert_eq!(iter.next(), Some(WalkEvent::Up(n1)));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`traverse`]: struct.NodeId.html#method.traverse
+    /// [`Down`]: enum.WalkEvent.html#variant.Down
+    /// [`Next`]: enum.WalkEvent.html#variant.Next
+    /// [`Up`]: enum.WalkEvent.html#variant.Up
+    pub fn walk_events<T>(self, arena: &Arena<T>) -> impl Iterator<Item = WalkEvent> + '_ {
+        let mut previous = None;
+        self.traverse(arena).filter_map(move |edge| {
+            let event = match (previous, edge) {
+                // After the end of a node, the walk moves to its sibling.
+                (Some(NodeEdge::End(_)), NodeEdge::Start(id)) => Some(WalkEvent::Next(id)),
+                (_, NodeEdge::Start(id)) => Some(WalkEvent::Down(id)),
+                // The end of a leaf directly follows its start.
+                (Some(NodeEdge::Start(start)), NodeEdge::End(id)) if start == id => None,
+                (_, NodeEdge::End(id)) => Some(WalkEvent::Up(id)),
+            };
+            previous = Some(edge);
+            event
+        })
+    }
+
+    /// Returns the node following this one in the pre-order of the subtree of
+    /// `root`, i.e. the node [`descendants`] of `root` yields after this one.
+    ///
+    /// Returns `None` if this node is the last one of the subtree. This node
+    /// is expected to be `root` itself or one of its descendants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// assert_eq!(n1.next_in_preorder(n1, &arena), Some(n1_1));
+    /// assert_eq!(n1_1_1.next_in_preorder(n1, &arena), Some(n1_2));
+    /// assert_eq!(n1_2.next_in_preorder(n1, &arena), None);
+    /// assert_eq!(n1_1_1.next_in_preorder(n1_1, &arena), None);
+    /// ```
+    ///
+    /// [`descendants`]: struct.NodeId.html#method.descendants
+    pub fn next_in_preorder<T>(self, root: NodeId, arena: &Arena<T>) -> Option<NodeId> {
+        let mut edge = NodeEdge::Start(self);
+        loop {
+            edge = edge.next_traverse(arena)?;
+            match edge {
+                NodeEdge::Start(id) => return Some(id),
+                NodeEdge::End(id) if id == root => return None,
+                NodeEdge::End(_) => {}
+            }
+        }
+    }
+
+    /// Returns the node preceding this one in the pre-order of the subtree of
+    /// `root`, i.e. the node [`descendants`] of `root` yields before this one.
+    ///
+    /// Returns `None` if this node is `root`. This node is expected to be
+    /// `root` itself or one of its descendants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// assert_eq!(n1_2.prev_in_preorder(n1, &arena), Some(n1_1_1));
+    /// assert_eq!(n1_1.prev_in_preorder(n1, &arena), Some(n1));
+    /// assert_eq!(n1.prev_in_preorder(n1, &arena), None);
+    /// assert_eq!(n1_1.prev_in_preorder(n1_1, &arena), None);
+    /// ```
+    ///
+    /// [`descendants`]: struct.NodeId.html#method.descendants
+    pub fn prev_in_preorder<T>(self, root: NodeId, arena: &Arena<T>) -> Option<NodeId> {
+        if self == root {
+            return None;
+        }
+        let mut edge = NodeEdge::Start(self);
+        loop {
+            edge = edge.prev_traverse(arena)?;
+            if let NodeEdge::Start(id) = edge {
+                return Some(id);
+            }
+        }
+    }
+
+    /// An iterator of the "sides" of a node visited during a depth-first
+    /// pre-order traversal (as [`traverse`]), each tagged with the index of the
+    /// node among its siblings.
+    ///
+    /// The start and the end of a node carry the same index. The index of this
+    /// node is its position among its own siblings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeEdge};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1                                                // #1, #8
+    /// //     |-- 1_1                                          // #2, #5
+    /// //     |   `-- 1_1_1                                    // #3, #4
+    /// //     `-- 1_2                                          // #6, #7
+    ///
+    /// let mut iter = n1.traverse_indexed(&arena);
+    /// assert_eq!(iter.next(), Some((NodeEdge::Start(n1), 0)));     // #1
+    /// assert_eq!(iter.next(), Some((NodeEdge::Start(n1_1), 0)));   // #2
+    /// assert_eq!(iter.next(), Some((NodeEdge::Start(n1_1_1), 0))); // #3
+    /// assert_eq!(iter.next(), Some((NodeEdge::End(n1_1_1), 0)));   // #4
+    /// assert_eq!(iter.next(), Some((NodeEdge::End(n1_1), 0)));     // #5
+    /// assert_eq!(iter.next(), Some((NodeEdge::Start(n1_2), 1)));   // #6
+    /// assert_eq!(iter.next(), Some((NodeEdge::End(n1_2), 1)));     // #7
+    /// assert_eq!(iter.next(), Some((NodeEdge::End(n1), 0)));       // #8
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`traverse`]: struct.NodeId.html#method.traverse
+    pub fn traverse_indexed<T>(
+        self,
+        arena: &Arena<T>,
+    ) -> impl Iterator<Item = (NodeEdge, usize)> + '_ {
+        let root_index = self.preceding_siblings(arena).count() - 1;
+        // Index of each open node, and the index of its next child.
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        self.traverse(arena).map(move |edge| match edge {
+            NodeEdge::Start(_) => {
+                let index = match stack.last_mut() {
+                    Some((_, next_child)) => {
+                        *next_child += 1;
+                        *next_child - 1
+                    }
+                    None => root_index,
+                };
+                stack.push((index, 0));
+                (edge, index)
+            }
+            NodeEdge::End(_) => {
+                let (index, _) = stack.pop().expect("each end should match a start");
+                (edge, index)
+            }
+        })
+    }
+
+    /// An iterator of the "sides" of a node visited during a depth-first pre-order traversal,
+    /// where nodes are visited end to start and children are visited in reverse insertion order.
+    ///
+    /// i.e. node.end -> second child -> first child -> node.start
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeEdge};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1                                                // #1, #10
+    /// //     |-- 1_1                                          // #6, #9
+    /// //     |   `-- 1_1_1                                    // #7, #8
+    /// //     |-- 1_2                                          // #4, #5
+    /// //     `-- 1_3                                          // #2, #3
+    ///
+    /// let mut iter = n1.reverse_traverse(&arena);
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1)));       // #1
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_3)));     // #2
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_3)));   // #3
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_2)));     // #4
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_2)));   // #5
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1)));     // #6
+    /// assert_eq!(iter.next(), Some(NodeEdge::End(n1_1_1)));   // #7
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1_1))); // #8
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1_1)));   // #9
+    /// assert_eq!(iter.next(), Some(NodeEdge::Start(n1)));     // #10
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeEdge};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1                                                // #1, #10
+    /// //     |-- 1_1                                          // #6, #9
+    /// //     |   `-- 1_1_1                                    // #7, #8
+    /// //     |-- 1_2                                          // #4, #5
+    /// //     `-- 1_3                                          // #2, #3
+    /// let traverse = n1.traverse(&arena).collect::<Vec<_>>();
+    /// let mut reverse = n1.reverse_traverse(&arena).collect::<Vec<_>>();
+    /// reverse.reverse();
+    /// assert_eq!(traverse, reverse);
+    /// ```
+    pub fn reverse_traverse<T>(self, arena: &Arena<T>) -> ReverseTraverse<'_, T> {
+        ReverseTraverse::new(arena, self)
+    }
+
+    /// Walks the subtree of this node, calling the visitor when entering and
+    /// leaving each node.
+    ///
+    /// The depth passed to the visitor is relative to this node (depth 0).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeId, TreeVisitor};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// struct Xml(String);
+    ///
+    /// impl TreeVisitor<&str> for Xml {
+    ///     fn enter(&mut self, _id: NodeId, data: &&str, _depth: usize) {
+    ///         self.0.push_str(&format!("<n{}>", data));
+    ///     }
+    ///
+    ///     fn leave(&mut self, _id: NodeId, data: &&str, _depth: usize) {
+    ///         self.0.push_str(&format!("</n{}>", data));
+    ///     }
+    /// }
+    ///
+    /// let mut xml = Xml(String::new());
+    /// n1.walk(&arena, &mut xml);
+    /// assert_eq!(xml.0, "<n1><n1_1><n1_1_1></n1_1_1></n1_1><n1_2></n1_2></n1>");
+    /// ```
+    pub fn walk<T, V>(self, arena: &Arena<T>, visitor: &mut V)
+    where
+        V: TreeVisitor<T>,
+    {
+        let mut depth = 0;
+        for edge in self.traverse(arena) {
+            match edge {
+                NodeEdge::Start(id) => {
+                    visitor.enter(id, arena[id].get(), depth);
+                    depth += 1;
+                }
+                NodeEdge::End(id) => {
+                    depth -= 1;
+                    visitor.leave(id, arena[id].get(), depth);
+                }
+            }
+        }
+    }
+
+    /// Detaches a node from its parent and siblings. Children are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, NodeEdge};
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- (implicit)
+    /// //     `-- 1
+    /// //         |-- 1_1
+    /// //         |   `-- 1_1_1
+    /// //         |-- 1_2 *
+    /// //         `-- 1_3
+    ///
+    /// n1_2.detach(&mut arena);
+    /// // arena
+    /// // |-- (implicit)
+    /// // |   `-- 1
+    /// // |       |-- 1_1
+    /// // |       |   `-- 1_1_1
+    /// // |       `-- 1_3
+    /// // `-- (implicit)
+    /// //     `-- 1_2 *
+    ///
+    /// assert!(arena[n1_2].parent().is_none());
+    /// assert!(arena[n1_2].previous_sibling().is_none());
+    /// assert!(arena[n1_2].next_sibling().is_none());
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_1_1));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn detach<T>(self, arena: &mut Arena<T>) {
+        arena.record(Op::Detach(self));
+        self.detach_unrecorded(arena);
+    }
+
+    /// Detaches a node from its parent and siblings, without recording the
+    /// operation.
+    ///
+    /// Used by operations which detach a node as part of a larger change.
+    pub(crate) fn detach_unrecorded<T>(self, arena: &mut Arena<T>) {
+        let range = SiblingsRange::new(self, self).detach_from_siblings(arena);
+        range
+            .rewrite_parents(arena, None)
+            .expect("Should never happen: `None` as parent is always valid");
+
+        // Ensure the node is surely detached.
+        debug_assert!(
+            arena[self].is_detached(),
+            "The node should be successfully detached"
+        );
+    }
+
+    /// Detaches the node (with its descendants) from its parent and siblings,
+    /// promoting it to a root of its own tree in the same arena.
+    ///
+    /// This is the same as [`detach`], but returns `self` for readability in
+    /// code that means "promote to root".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    ///
+    /// let root = n1_1.make_root(&mut arena);
+    /// assert_eq!(root, n1_1);
+    /// assert_eq!(arena[root].parent(), None);
+    /// assert_eq!(arena[root].first_child(), Some(n1_1_1));
+    /// assert_eq!(n1.children(&arena).count(), 0);
+    /// ```
+    ///
+    /// [`detach`]: struct.NodeId.html#method.detach
+    pub fn make_root<T>(self, arena: &mut Arena<T>) -> NodeId {
+        self.detach(arena);
+        assert!(arena[self].parent.is_none(), "The node should be a root");
+        self
+    }
+
+    /// Appends a new child to this node, after existing children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    ///
+    /// * the given new child is `self`, or
+    /// * the given new child is an ancestor of `self`, or
+    /// * the current node or the given new child was already [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = arena.new_node("1_1");
+    /// n1.append(n1_1, &mut arena);
+    /// let n1_2 = arena.new_node("1_2");
+    /// n1.append(n1_2, &mut arena);
+    /// let n1_3 = arena.new_node("1_3");
+    /// n1.append(n1_3, &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2
+    /// //     `-- 1_3
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn append<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
+        self.checked_append(new_child, arena)
+            .expect("Preconditions not met: invalid argument");
+    }
+
+    /// Appends a new child to this node, after existing children.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::AppendSelf`] error if the given new child is
+    ///   `self`.
+    /// * Returns [`NodeError::AppendAncestor`] error if the given new child is
+    ///   an ancestor of `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if the given new child or `self` is [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert!(n1.checked_append(n1, &mut arena).is_err());
+    ///
+    /// let n1_1 = arena.new_node("1_1");
+    /// assert!(n1.checked_append(n1_1, &mut arena).is_ok());
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`NodeError::AppendSelf`]: enum.NodeError.html#variant.AppendSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn checked_append<T>(
+        self,
+        new_child: NodeId,
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if new_child == self {
+            return Err(NodeError::AppendSelf);
+        }
+        if arena[self].is_removed() {
+            return Err(NodeError::RemovedNode(self));
+        }
+        if arena[new_child].is_removed() {
+            return Err(NodeError::RemovedNode(new_child));
+        }
+        if self.ancestors(arena).any(|ancestor| new_child == ancestor) {
+            return Err(NodeError::AppendAncestor);
+        }
+        if arena[self].last_child == Some(new_child) {
+            // Already in place.
+            return Ok(());
+        }
+        arena.record(Op::Append {
+            parent: self,
+            child: new_child,
+        });
+        new_child.detach_unrecorded(arena);
+        insert_with_neighbors(arena, new_child, Some(self), arena[self].last_child, None)
+            .expect("Should never fail: `new_child` is not `self` and they are not removed");
+
+        Ok(())
+    }
+
+    /// Creates and appends a new node (from its associated data) as the last child.
+    /// This method is a fast path for the common case of appending a new node. It is quicker than [`append`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already has `usize::max_value()` nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    /// let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //  1_1_1 --|-- 1_1_2
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_1_1));
+    /// assert_eq!(iter.next(), Some(n1_1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    /// [`append`]: struct.NodeId.html#method.append
+    pub fn append_value<T>(self, value: T, arena: &mut Arena<T>) -> NodeId {
+        let new_child = arena.new_node(value);
+        self.append_new_node_unchecked(new_child, arena);
+
+        new_child
+    }
+
+    /// Creates and appends a new node holding `T::default()` as the last
+    /// child.
+    ///
+    /// This is handy when building the structure first and filling the data
+    /// later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already has `usize::max_value()` nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node(String::from("1"));
+    /// let n1_1 = n1.append_default(&mut arena);
+    /// assert_eq!(arena[n1_1].get(), "");
+    ///
+    /// arena[n1_1].get_mut().push_str("1_1");
+    /// assert_eq!(arena[n1].last_child(), Some(n1_1));
+    /// ```
+    pub fn append_default<T: Default>(self, arena: &mut Arena<T>) -> NodeId {
+        self.append_value(T::default(), arena)
+    }
+
+    /// Appends a new child to this node, after all existing children (if any).
+    /// This method is a fast path for the common case of appending a new node.
+    /// `new_child` requirements:
+    /// 1. Must be detached. No parents or siblings.
+    /// 2. Has not been [`remove`]d.
+    /// 3. `append_new_node()` was not called on itself
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert!(n1.checked_append(n1, &mut arena).is_err());
+    ///
+    /// let n1_1 = arena.new_node("1_1");
+    /// assert!(n1.checked_append(n1_1, &mut arena).is_ok());
+    /// ```
+    ///
+    /// [`remove`]: struct.NodeId.html#method.remove
+    fn append_new_node_unchecked<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
+        arena.record(Op::Append {
+            parent: self,
+            child: new_child,
+        });
+        insert_last_unchecked(arena, new_child, self);
+    }
+
+    /// Prepends a new child to this node, before existing children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    ///
+    /// * the given new child is `self`, or
+    /// * the given new child is an ancestor of `self`, or
+    /// * the current node or the given new child was already [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = arena.new_node("1_1");
+    /// n1.prepend(n1_1, &mut arena);
+    /// let n1_2 = arena.new_node("1_2");
+    /// n1.prepend(n1_2, &mut arena);
+    /// let n1_3 = arena.new_node("1_3");
+    /// n1.prepend(n1_3, &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_3
+    /// //     |-- 1_2
+    /// //     `-- 1_1
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn prepend<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
+        self.checked_prepend(new_child, arena)
+            .expect("Preconditions not met: invalid argument");
+    }
+
+    /// Prepends a new child to this node, before existing children.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::PrependSelf`] error if the given new child is
+    ///   `self`.
+    /// * Returns [`NodeError::PrependAncestor`] error if the given new child is
+    ///   an ancestor of `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if the given new child or `self` is [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert!(n1.checked_prepend(n1, &mut arena).is_err());
+    ///
+    /// let n1_1 = arena.new_node("1_1");
+    /// assert!(n1.checked_prepend(n1_1, &mut arena).is_ok());
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`NodeError::PrependSelf`]: enum.NodeError.html#variant.PrependSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn checked_prepend<T>(
+        self,
+        new_child: NodeId,
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if new_child == self {
+            return Err(NodeError::PrependSelf);
+        }
+        if arena[self].is_removed() {
+            return Err(NodeError::RemovedNode(self));
+        }
+        if arena[new_child].is_removed() {
+            return Err(NodeError::RemovedNode(new_child));
+        }
+        if self.ancestors(arena).any(|ancestor| new_child == ancestor) {
+            return Err(NodeError::PrependAncestor);
+        }
+        if arena[self].first_child == Some(new_child) {
+            // Already in place.
+            return Ok(());
+        }
+        arena.record(Op::Prepend {
+            parent: self,
+            child: new_child,
+        });
+        insert_with_neighbors(arena, new_child, Some(self), None, arena[self].first_child)
+            .expect("Should never fail: `new_child` is not `self` and they are not removed");
+
+        Ok(())
+    }
+
+    /// Inserts a new sibling after this node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    ///
+    /// * the given new sibling is `self`, or
+    /// * the current node or the given new sibling was already [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1 *
+    /// //     `-- 1_2
+    ///
+    /// let n1_3 = arena.new_node("1_3");
+    /// n1_1.insert_after(n1_3, &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_3 *
+    /// //     `-- 1_2
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn insert_after<T>(self, new_sibling: NodeId, arena: &mut Arena<T>) {
+        self.checked_insert_after(new_sibling, arena)
+            .expect("Preconditions not met: invalid argument");
+    }
+
+    /// Inserts a new sibling after this node.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::InsertAfterSelf`] error if the given new sibling
+    ///   is `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if the given new sibling or `self` is [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert!(n1.checked_insert_after(n1, &mut arena).is_err());
+    ///
+    /// let n2 = arena.new_node("2");
+    /// assert!(n1.checked_insert_after(n2, &mut arena).is_ok());
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`NodeError::InsertAfterSelf`]: enum.NodeError.html#variant.InsertAfterSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn checked_insert_after<T>(
+        self,
+        new_sibling: NodeId,
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if new_sibling == self {
+            return Err(NodeError::InsertAfterSelf);
+        }
+        if arena[self].is_removed() {
+            return Err(NodeError::RemovedNode(self));
+        }
+        if arena[new_sibling].is_removed() {
+            return Err(NodeError::RemovedNode(new_sibling));
+        }
+        if arena[self].next_sibling == Some(new_sibling) {
+            // Already in place.
+            return Ok(());
+        }
+        arena.record(Op::InsertAfter {
+            node: self,
+            sibling: new_sibling,
+        });
+        new_sibling.detach_unrecorded(arena);
+        let (next_sibling, parent) = {
+            let current = &arena[self];
+            (current.next_sibling, current.parent)
+        };
+        insert_with_neighbors(arena, new_sibling, parent, Some(self), next_sibling)
+            .expect("Should never fail: `new_sibling` is not `self` and they are not removed");
+
+        Ok(())
+    }
+
+    /// Inserts a new sibling before this node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    ///
+    /// * the given new sibling is `self`, or
+    /// * the current node or the given new sibling was already [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = arena.new_node("1_1");
+    /// n1.append(n1_1, &mut arena);
+    /// let n1_2 = arena.new_node("1_2");
+    /// n1.append(n1_2, &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     `-- 1_2 *
+    ///
+    /// let n1_3 = arena.new_node("1_3");
+    /// n1_2.insert_before(n1_3, &mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_3 *
+    /// //     `-- 1_2
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn insert_before<T>(self, new_sibling: NodeId, arena: &mut Arena<T>) {
+        self.checked_insert_before(new_sibling, arena)
+            .expect("Preconditions not met: invalid argument");
+    }
+
+    /// Inserts a new sibling before this node.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::InsertBeforeSelf`] error if the given new sibling
+    ///   is `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if the given new sibling or `self` is [`remove`]d.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// assert!(n1.checked_insert_before(n1, &mut arena).is_err());
+    ///
+    /// let n2 = arena.new_node("2");
+    /// assert!(n1.checked_insert_before(n2, &mut arena).is_ok());
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`NodeError::InsertBeforeSelf`]: enum.NodeError.html#variant.InsertBeforeSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn checked_insert_before<T>(
+        self,
+        new_sibling: NodeId,
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if new_sibling == self {
+            return Err(NodeError::InsertBeforeSelf);
+        }
+        if arena[self].is_removed() {
+            return Err(NodeError::RemovedNode(self));
+        }
+        if arena[new_sibling].is_removed() {
+            return Err(NodeError::RemovedNode(new_sibling));
+        }
+        if arena[self].previous_sibling == Some(new_sibling) {
+            // Already in place.
+            return Ok(());
+        }
+        arena.record(Op::InsertBefore {
+            node: self,
+            sibling: new_sibling,
+        });
+        new_sibling.detach_unrecorded(arena);
+        let (previous_sibling, parent) = {
+            let current = &arena[self];
+            (current.previous_sibling, current.parent)
+        };
+        insert_with_neighbors(arena, new_sibling, parent, previous_sibling, Some(self))
+            .expect("Should never fail: `new_sibling` is not `self` and they are not removed");
+
+        Ok(())
+    }
+
+    /// Inserts this node between the two given adjacent siblings.
+    ///
+    /// `next` must be the next sibling of `prev`. This node is detached from
+    /// its current place first.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::InsertAfterSelf`] or
+    ///   [`NodeError::InsertBeforeSelf`] error if `prev` or `next` is `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if any of the nodes is [`remove`]d.
+    /// * Returns [`NodeError::NotAdjacentSiblings`] error if `next` is not the
+    ///   next sibling of `prev`.
+    /// * Returns [`NodeError::AppendAncestor`] error if `self` is an ancestor
+    ///   of the siblings.
+    ///
+    /// The tree is left untouched on failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2
+    /// //     `-- 1_3
+    ///
+    /// let new = arena.new_node("new");
+    /// assert!(new.insert_between(n1_1, n1_2, &mut arena).is_ok());
+    /// assert!(new.insert_between(n1_1, n1_3, &mut arena).is_err());
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- new
+    /// //     |-- 1_2
+    /// //     `-- 1_3
+    ///
+    /// let mut iter = n1.children(&arena);
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(new));
+    /// assert_eq!(iter.next(), Some(n1_2));
+    /// assert_eq!(iter.next(), Some(n1_3));
     /// assert_eq!(iter.next(), None);
     /// ```
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`NodeError::InsertAfterSelf`]: enum.NodeError.html#variant.InsertAfterSelf
+    /// [`NodeError::InsertBeforeSelf`]: enum.NodeError.html#variant.InsertBeforeSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`NodeError::NotAdjacentSiblings`]: enum.NodeError.html#variant.NotAdjacentSiblings
+    /// [`NodeError::AppendAncestor`]: enum.NodeError.html#variant.AppendAncestor
     /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn append<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
-        self.checked_append(new_child, arena)
-            .expect("Preconditions not met: invalid argument");
+    pub fn insert_between<T>(
+        self,
+        prev: NodeId,
+        next: NodeId,
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if prev == self {
+            return Err(NodeError::InsertAfterSelf);
+        }
+        if next == self {
+            return Err(NodeError::InsertBeforeSelf);
+        }
+        for id in [self, prev, next] {
+            if arena[id].is_removed() {
+                return Err(NodeError::RemovedNode(id));
+            }
+        }
+        if arena[prev].next_sibling != Some(next) {
+            return Err(NodeError::NotAdjacentSiblings);
+        }
+        let parent = arena[prev].parent;
+        if parent.is_some_and(|parent| parent.ancestors(arena).any(|id| id == self)) {
+            return Err(NodeError::AppendAncestor);
+        }
+
+        arena.record(Op::InsertAfter {
+            node: prev,
+            sibling: self,
+        });
+        self.detach_unrecorded(arena);
+        insert_with_neighbors(arena, self, parent, Some(prev), Some(next))
+            .expect("Should never fail: neighbors are adjacent and `self` is not their ancestor");
+
+        Ok(())
+    }
+
+    /// Moves this node to be the previous sibling of `target`.
+    ///
+    /// This node is detached from its current place, along with its
+    /// descendants, and reinserted right before `target`, under the parent of
+    /// `target`. This is [`checked_insert_before`] seen from the moved node,
+    /// with an additional check that the move does not create a cycle.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::InsertBeforeSelf`] error if `target` is `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if `self` or `target` is [`remove`]d.
+    /// * Returns [`NodeError::AppendAncestor`] error if `target` is a
+    ///   descendant of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    /// let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    ///
+    /// assert!(n1_1.move_before(n1_2_1, &mut arena).is_ok());
+    /// assert_eq!(arena[n1_1].parent(), Some(n1_2));
+    /// let children = n1_2.children(&arena).collect::<Vec<_>>();
+    /// assert_eq!(children, [n1_1, n1_2_1]);
+    ///
+    /// assert!(n1_2.move_before(n1_2_1, &mut arena).is_err());
+    /// ```
+    ///
+    /// [`checked_insert_before`]: struct.NodeId.html#method.checked_insert_before
+    /// [`NodeError::InsertBeforeSelf`]: enum.NodeError.html#variant.InsertBeforeSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`NodeError::AppendAncestor`]: enum.NodeError.html#variant.AppendAncestor
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn move_before<T>(self, target: NodeId, arena: &mut Arena<T>) -> Result<(), NodeError> {
+        self.check_move(target, NodeError::InsertBeforeSelf, arena)?;
+        target.checked_insert_before(self, arena)
+    }
+
+    /// Moves this node to be the next sibling of `target`.
+    ///
+    /// This node is detached from its current place, along with its
+    /// descendants, and reinserted right after `target`, under the parent of
+    /// `target`. This is [`checked_insert_after`] seen from the moved node,
+    /// with an additional check that the move does not create a cycle.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::InsertAfterSelf`] error if `target` is `self`.
+    /// * Returns [`NodeError::RemovedNode`] error, carrying the ID of the
+    ///   removed node, if `self` or `target` is [`remove`]d.
+    /// * Returns [`NodeError::AppendAncestor`] error if `target` is a
+    ///   descendant of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    /// let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    ///
+    /// assert!(n1_1.move_after(n1_2_1, &mut arena).is_ok());
+    /// assert_eq!(arena[n1_1].parent(), Some(n1_2));
+    /// let children = n1_2.children(&arena).collect::<Vec<_>>();
+    /// assert_eq!(children, [n1_2_1, n1_1]);
+    ///
+    /// assert!(n1_2.move_after(n1_2_1, &mut arena).is_err());
+    /// ```
+    ///
+    /// [`checked_insert_after`]: struct.NodeId.html#method.checked_insert_after
+    /// [`NodeError::InsertAfterSelf`]: enum.NodeError.html#variant.InsertAfterSelf
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`NodeError::AppendAncestor`]: enum.NodeError.html#variant.AppendAncestor
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn move_after<T>(self, target: NodeId, arena: &mut Arena<T>) -> Result<(), NodeError> {
+        self.check_move(target, NodeError::InsertAfterSelf, arena)?;
+        target.checked_insert_after(self, arena)
+    }
+
+    /// Checks that this node can be moved next to `target`, returning
+    /// `self_error` if `target` is `self`.
+    fn check_move<T>(
+        self,
+        target: NodeId,
+        self_error: NodeError,
+        arena: &Arena<T>,
+    ) -> Result<(), NodeError> {
+        if target == self {
+            return Err(self_error);
+        }
+        for id in [self, target] {
+            if arena[id].is_removed() {
+                return Err(NodeError::RemovedNode(id));
+            }
+        }
+        if target.ancestors(arena).any(|id| id == self) {
+            return Err(NodeError::AppendAncestor);
+        }
+        Ok(())
+    }
+
+    /// Swaps the positions of this node and `other` among their siblings.
+    ///
+    /// Both nodes must share the same parent, or be part of the same chain of
+    /// siblings without a parent. Only the sibling relations (and the first
+    /// and last child of the parent) are rewritten, so no cycle check is
+    /// needed. Swapping a node with itself does nothing.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::RemovedNode`] error if `self` or `other` is
+    ///   [`remove`]d.
+    /// * Returns [`NodeError::NotSiblings`] error if `self` and `other` do not
+    ///   share a parent, e.g. if they are unrelated roots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    /// let n1_3 = n1.append_value("1_3", &mut arena);
+    ///
+    /// assert!(n1_1.swap_siblings(n1_3, &mut arena).is_ok());
+    /// let children = n1.children(&arena).collect::<Vec<_>>();
+    /// assert_eq!(children, [n1_3, n1_2, n1_1]);
+    ///
+    /// assert!(n1.swap_siblings(n1_2, &mut arena).is_err());
+    /// ```
+    ///
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`NodeError::NotSiblings`]: enum.NodeError.html#variant.NotSiblings
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn swap_siblings<T>(self, other: NodeId, arena: &mut Arena<T>) -> Result<(), NodeError> {
+        for id in [self, other] {
+            if arena[id].is_removed() {
+                return Err(NodeError::RemovedNode(id));
+            }
+        }
+        if self == other {
+            return Ok(());
+        }
+        let parent = arena[self].parent;
+        let siblings = match parent {
+            Some(_) => arena[other].parent == parent,
+            // Nodes without a parent are only siblings if they are linked.
+            None => {
+                self.preceding_siblings(arena).any(|id| id == other)
+                    || self.following_siblings(arena).any(|id| id == other)
+            }
+        };
+        if !siblings {
+            return Err(NodeError::NotSiblings);
+        }
+
+        // Make `first` the node that precedes when the two are adjacent.
+        let (first, second) = if arena[other].next_sibling == Some(self) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let first_prev = arena[first].previous_sibling;
+        let first_next = arena[first].next_sibling;
+        let second_prev = arena[second].previous_sibling;
+        let second_next = arena[second].next_sibling;
+
+        // Recorded as moving `second` before `first`, then `first` to the
+        // former place of `second`.
+        arena.record(Op::InsertBefore {
+            node: first,
+            sibling: second,
+        });
+        if first_next != Some(second) {
+            arena.record(match (second_prev, second_next) {
+                (Some(node), _) => Op::InsertAfter {
+                    node,
+                    sibling: first,
+                },
+                (None, Some(node)) => Op::InsertBefore {
+                    node,
+                    sibling: first,
+                },
+                (None, None) => unreachable!("`second` has at least `first` as a sibling"),
+            });
+        }
+
+        if first_next == Some(second) {
+            connect_neighbors(arena, parent, first_prev, Some(second));
+            connect_neighbors(arena, parent, Some(second), Some(first));
+            connect_neighbors(arena, parent, Some(first), second_next);
+        } else {
+            connect_neighbors(arena, parent, first_prev, Some(second));
+            connect_neighbors(arena, parent, Some(second), first_next);
+            connect_neighbors(arena, parent, second_prev, Some(first));
+            connect_neighbors(arena, parent, Some(first), second_next);
+        }
+
+        Ok(())
+    }
+
+    /// Reorders the children of this node to match the given order.
+    ///
+    /// `order` must be a permutation of the current children of this node.
+    /// Only the sibling relations are rewritten, the children themselves and
+    /// their descendants are untouched.
+    ///
+    /// # Failures
+    ///
+    /// * Returns [`NodeError::RemovedNode`] error if `self` is [`remove`]d.
+    /// * Returns [`NodeError::InvalidPermutation`] error if `order` is not a
+    ///   permutation of the children of `self`. The children are left
+    ///   untouched in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// let n1_2 = n1.append_value("1_2", &mut arena);
+    /// let n1_3 = n1.append_value("1_3", &mut arena);
+    ///
+    /// assert!(n1.reorder_children(&[n1_3, n1_1, n1_2], &mut arena).is_ok());
+    /// let children = n1.children(&arena).collect::<Vec<_>>();
+    /// assert_eq!(children, [n1_3, n1_1, n1_2]);
+    ///
+    /// assert!(n1.reorder_children(&[n1_3, n1_1], &mut arena).is_err());
+    /// ```
+    ///
+    /// [`NodeError::RemovedNode`]: enum.NodeError.html#variant.RemovedNode
+    /// [`NodeError::InvalidPermutation`]: enum.NodeError.html#variant.InvalidPermutation
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn reorder_children<T>(
+        self,
+        order: &[NodeId],
+        arena: &mut Arena<T>,
+    ) -> Result<(), NodeError> {
+        if arena[self].is_removed() {
+            return Err(NodeError::RemovedNode(self));
+        }
+        if order.len() != self.children(arena).count()
+            || order.iter().any(|&child| {
+                arena.get(child).map(|node| node.parent) != Some(Some(self))
+                    || child.is_removed(arena)
+            })
+        {
+            return Err(NodeError::InvalidPermutation);
+        }
+        let mut sorted = order.iter().map(|id| id.index0()).collect::<Vec<_>>();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(NodeError::InvalidPermutation);
+        }
+        if self.children(arena).eq(order.iter().copied()) {
+            // Already in order.
+            return Ok(());
+        }
+
+        for &child in order {
+            arena.record(Op::Append {
+                parent: self,
+                child,
+            });
+        }
+
+        let mut previous = None;
+        for &child in order {
+            arena[child].previous_sibling = previous;
+            if let Some(previous) = previous {
+                arena[previous].next_sibling = Some(child);
+            }
+            previous = Some(child);
+        }
+        if let Some(last) = previous {
+            arena[last].next_sibling = None;
+        }
+        let node = &mut arena[self];
+        node.first_child = order.first().copied();
+        node.last_child = order.last().copied();
+
+        if cfg!(debug_assertions) {
+            for pair in order.windows(2) {
+                debug_assert_triangle_nodes!(arena, Some(self), Some(pair[0]), Some(pair[1]));
+            }
+            debug_assert_triangle_nodes!(arena, Some(self), None, arena[self].first_child);
+            debug_assert_triangle_nodes!(arena, Some(self), arena[self].last_child, None);
+        }
+
+        Ok(())
+    }
+
+    /// Merges runs of adjacent leaf children of this node, in the manner of
+    /// `Itertools::coalesce`.
+    ///
+    /// The children are walked in order, and for each pair of adjacent
+    /// children that are both leaves, `combine` is called with the data of
+    /// the left and the right node. If it returns `true`, the right node is
+    /// considered absorbed into the left one and is [`remove`]d, and the left
+    /// node is then paired with its new next sibling. If it returns `false`,
+    /// the walk moves on to the right node.
+    ///
+    /// The data of the right node is passed by mutable reference, so that
+    /// `combine` can move parts of it out (e.g. with [`mem::take`]) without
+    /// losing it when it declines to merge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let p = arena.new_node(String::from("p"));
+    /// p.append_value(String::from("Hello"), &mut arena);
+    /// p.append_value(String::from(", "), &mut arena);
+    /// p.append_value(String::from("world"), &mut arena);
+    /// let b = p.append_value(String::from("b"), &mut arena);
+    /// b.append_value(String::from("!"), &mut arena);
+    ///
+    /// p.coalesce_children(&mut arena, |left, right| {
+    ///     left.push_str(&std::mem::take(right));
+    ///     true
+    /// });
+    ///
+    /// let children = p.children(&arena).map(|id| arena[id].get().as_str()).collect::<Vec<_>>();
+    /// assert_eq!(children, ["Hello, world", "b"]);
+    /// ```
+    ///
+    /// [`remove`]: struct.NodeId.html#method.remove
+    /// [`mem::take`]: https://doc.rust-lang.org/stable/std/mem/fn.take.html
+    pub fn coalesce_children<T, F>(self, arena: &mut Arena<T>, mut combine: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut current = arena[self].first_child;
+        while let Some(left) = current {
+            let Some(right) = arena[left].next_sibling else {
+                break;
+            };
+            let both_leaves =
+                arena[left].first_child.is_none() && arena[right].first_child.is_none();
+            if both_leaves {
+                let [left_node, right_node] = arena
+                    .get_disjoint_mut([left, right])
+                    .expect("siblings should be distinct nodes");
+                if combine(left_node.get_mut(), right_node.get_mut()) {
+                    right.remove(arena);
+                    continue;
+                }
+            }
+            current = Some(right);
+        }
+    }
+
+    /// Predicts what [`remove`] would do with the children of this node,
+    /// without mutating the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::{Arena, RemovalEffect};
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("1");
+    /// let n1_1 = n1.append_value("1_1", &mut arena);
+    /// n1_1.append_value("1_1_1", &mut arena);
+    ///
+    /// assert_eq!(n1_1.removal_effect(&arena), RemovalEffect::SpliceToParent(n1));
+    /// assert_eq!(n1.removal_effect(&arena), RemovalEffect::PromoteChildrenToRoots);
+    /// ```
+    ///
+    /// [`remove`]: struct.NodeId.html#method.remove
+    pub fn removal_effect<T>(self, arena: &Arena<T>) -> RemovalEffect {
+        match arena[self].parent {
+            Some(parent) => RemovalEffect::SpliceToParent(parent),
+            None => RemovalEffect::PromoteChildrenToRoots,
+        }
+    }
+
+    /// Removes a node from the arena.
+    ///
+    /// Children of the removed node will be inserted, in order, to the place
+    /// where the removed node was. Use [`remove_splice_at_end`] to append them
+    /// after the last sibling instead.
+    ///
+    /// Please note that the node will not be removed from the internal arena
+    /// storage, but marked as `removed`. Traversing the arena returns a
+    /// plain iterator and contains removed elements too.
+    ///
+    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_2_2 = arena.new_node("1_2_2");
+    /// # n1_2.append(n1_2_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2 *
+    /// //     |   |-- 1_2_1
+    /// //     |   `-- 1_2_2
+    /// //     `-- 1_3
+    ///
+    /// n1_2.remove(&mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2_1
+    /// //     |-- 1_2_2
+    /// //     `-- 1_3
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_2_1));
+    /// assert_eq!(iter.next(), Some(n1_2_2));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
+    /// [`remove_splice_at_end`]: struct.NodeId.html#method.remove_splice_at_end
+    pub fn remove<T>(self, arena: &mut Arena<T>) {
+        debug_assert_triangle_nodes!(
+            arena,
+            arena[self].parent,
+            arena[self].previous_sibling,
+            Some(self)
+        );
+        debug_assert_triangle_nodes!(
+            arena,
+            arena[self].parent,
+            Some(self),
+            arena[self].next_sibling
+        );
+        debug_assert_triangle_nodes!(arena, Some(self), None, arena[self].first_child);
+        debug_assert_triangle_nodes!(arena, Some(self), arena[self].last_child, None);
+
+        // Retrieve needed values.
+        let (parent, previous_sibling, next_sibling, first_child, last_child) = {
+            let node = &arena[self];
+            (
+                node.parent,
+                node.previous_sibling,
+                node.next_sibling,
+                node.first_child,
+                node.last_child,
+            )
+        };
+
+        assert_eq!(first_child.is_some(), last_child.is_some());
+        arena.record(Op::Remove(self));
+        self.detach_unrecorded(arena);
+        if let (Some(first_child), Some(last_child)) = (first_child, last_child) {
+            let range = SiblingsRange::new(first_child, last_child).detach_from_siblings(arena);
+            range
+                .transplant(arena, parent, previous_sibling, next_sibling)
+                .expect("Should never fail: neighbors and children must be consistent");
+        }
+        arena.free_node(self);
+        debug_assert!(arena[self].is_detached());
     }
 
-    /// Appends a new child to this node, after existing children.
-    ///
-    /// # Failures
-    ///
-    /// * Returns [`NodeError::AppendSelf`] error if the given new child is
-    ///   `self`.
-    /// * Returns [`NodeError::AppendAncestor`] error if the given new child is
-    ///   an ancestor of `self`.
-    /// * Returns [`NodeError::Removed`] error if the given new child or `self`
-    ///   is [`remove`]d.
+    /// Removes a node from the arena, appending its children after the last
+    /// sibling of the removed node.
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// Unlike [`remove`], which inserts the children where the removed node
+    /// was, this moves them, in order, to the end of the children of the
+    /// parent.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// assert!(n1.checked_append(n1, &mut arena).is_err());
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_2_2 = arena.new_node("1_2_2");
+    /// # n1_2.append(n1_2_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2 *
+    /// //     |   |-- 1_2_1
+    /// //     |   `-- 1_2_2
+    /// //     `-- 1_3
     ///
-    /// let n1_1 = arena.new_node("1_1");
-    /// assert!(n1.checked_append(n1_1, &mut arena).is_ok());
+    /// n1_2.remove_splice_at_end(&mut arena);
+    ///
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_3
+    /// //     |-- 1_2_1
+    /// //     `-- 1_2_2
+    ///
+    /// let mut iter = n1.descendants(&arena);
+    /// assert_eq!(iter.next(), Some(n1));
+    /// assert_eq!(iter.next(), Some(n1_1));
+    /// assert_eq!(iter.next(), Some(n1_3));
+    /// assert_eq!(iter.next(), Some(n1_2_1));
+    /// assert_eq!(iter.next(), Some(n1_2_2));
+    /// assert_eq!(iter.next(), None);
     /// ```
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`NodeError::AppendSelf`]: enum.NodeError.html#variant.AppendSelf
-    /// [`NodeError::Removed`]: enum.NodeError.html#variant.Removed
     /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn checked_append<T>(
-        self,
-        new_child: NodeId,
-        arena: &mut Arena<T>,
-    ) -> Result<(), NodeError> {
-        if new_child == self {
-            return Err(NodeError::AppendSelf);
+    pub fn remove_splice_at_end<T>(self, arena: &mut Arena<T>) {
+        // Retrieve needed values.
+        let (parent, previous_sibling, first_child, last_child) = {
+            let node = &arena[self];
+            (
+                node.parent,
+                node.previous_sibling,
+                node.first_child,
+                node.last_child,
+            )
+        };
+
+        // Find the last sibling remaining once this node is detached.
+        let mut last_sibling = self;
+        while let Some(next_sibling) = arena[last_sibling].next_sibling {
+            last_sibling = next_sibling;
         }
-        if arena[self].is_removed() || arena[new_child].is_removed() {
-            return Err(NodeError::Removed);
+        let last_sibling = if last_sibling == self {
+            previous_sibling
+        } else {
+            Some(last_sibling)
+        };
+
+        assert_eq!(first_child.is_some(), last_child.is_some());
+        if arena.is_recording() {
+            // Recorded as moving the children one by one, then removing the
+            // node left without children.
+            let mut previous = last_sibling;
+            let mut cursor = first_child;
+            while let Some(child) = cursor {
+                cursor = arena[child].next_sibling;
+                arena.record(match (previous, parent) {
+                    (Some(node), _) => Op::InsertAfter {
+                        node,
+                        sibling: child,
+                    },
+                    (None, Some(parent)) => Op::Append { parent, child },
+                    (None, None) => Op::Detach(child),
+                });
+                previous = Some(child);
+            }
+            arena.record(Op::Remove(self));
         }
-        if self.ancestors(arena).any(|ancestor| new_child == ancestor) {
-            return Err(NodeError::AppendAncestor);
+        self.detach_unrecorded(arena);
+        if let (Some(first_child), Some(last_child)) = (first_child, last_child) {
+            let range = SiblingsRange::new(first_child, last_child).detach_from_siblings(arena);
+            range
+                .transplant(arena, parent, last_sibling, None)
+                .expect("Should never fail: neighbors and children must be consistent");
         }
-        new_child.detach(arena);
-        insert_with_neighbors(arena, new_child, Some(self), arena[self].last_child, None)
-            .expect("Should never fail: `new_child` is not `self` and they are not removed");
-
-        Ok(())
+        arena.free_node(self);
+        debug_assert!(arena[self].is_detached());
     }
 
-    /// Creates and appends a new node (from its associated data) as the last child.
-    /// This method is a fast path for the common case of appending a new node. It is quicker than [`append`].
-    ///
-    /// # Panics
-    ///
-    /// Panics if the arena already has `usize::max_value()` nodes.
-    ///
+    /// Removes a node and its descendants from the arena.
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// let n1_1 = n1.append_value("1_1", &mut arena);
-    /// let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
-    /// let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_2_2 = arena.new_node("1_2_2");
+    /// # n1_2.append(n1_2_2, &mut arena);
+    /// # let n1_3 = arena.new_node("1_3");
+    /// # n1.append(n1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |-- 1_2 *
+    /// //     |   |-- 1_2_1
+    /// //     |   `-- 1_2_2
+    /// //     `-- 1_3
+    ///
+    /// n1_2.remove_subtree(&mut arena);
     ///
     /// // arena
     /// // `-- 1
     /// //     |-- 1_1
-    /// //  1_1_1 --|-- 1_1_2
+    /// //     `-- 1_3
     ///
     /// let mut iter = n1.descendants(&arena);
     /// assert_eq!(iter.next(), Some(n1));
     /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_1_1));
-    /// assert_eq!(iter.next(), Some(n1_1_2));
+    /// assert_eq!(iter.next(), Some(n1_3));
     /// assert_eq!(iter.next(), None);
     /// ```
-    /// [`append`]: struct.NodeId.html#method.append
-    pub fn append_value<T>(self, value: T, arena: &mut Arena<T>) -> NodeId {
-        let new_child = arena.new_node(value);
-        self.append_new_node_unchecked(new_child, arena);
-
-        new_child
+    ///
+    pub fn remove_subtree<T>(self, arena: &mut Arena<T>) {
+        self.remove_subtree_count(arena);
     }
 
-    /// Appends a new child to this node, after all existing children (if any).
-    /// This method is a fast path for the common case of appending a new node.
-    /// `new_child` requirements:
-    /// 1. Must be detached. No parents or siblings.
-    /// 2. Has not been [`remove`]d.
-    /// 3. `append_new_node()` was not called on itself
+    /// Removes a node and its descendants from the arena, returning the
+    /// number of removed nodes (including the node itself).
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// assert!(n1.checked_append(n1, &mut arena).is_err());
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_2_2 = arena.new_node("1_2_2");
+    /// # n1_2.append(n1_2_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     `-- 1_2 *
+    /// //         |-- 1_2_1
+    /// //         `-- 1_2_2
     ///
-    /// let n1_1 = arena.new_node("1_1");
-    /// assert!(n1.checked_append(n1_1, &mut arena).is_ok());
+    /// assert_eq!(n1_2.remove_subtree_count(&mut arena), 3);
+    /// assert_eq!(n1.remove_subtree_count(&mut arena), 2);
     /// ```
-    ///
-    /// [`remove`]: struct.NodeId.html#method.remove
-    fn append_new_node_unchecked<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
-        insert_last_unchecked(arena, new_child, self);
+    pub fn remove_subtree_count<T>(self, arena: &mut Arena<T>) -> usize {
+        arena.record(Op::RemoveSubtree(self));
+        self.detach_unrecorded(arena);
+
+        // use a preorder traversal to remove node.
+        let mut count = 0;
+        let mut cursor = Some(self);
+        while let Some(id) = cursor {
+            arena.free_node(id);
+            count += 1;
+            let node = &arena[id];
+            cursor = node.first_child.or(node.next_sibling).or_else(|| {
+                id.ancestors(arena) // traverse ancestors upwards
+                    .skip(1) // skip the starting node itself
+                    .find(|n| arena[*n].next_sibling.is_some()) // first ancestor with a sibling
+                    .and_then(|n| arena[n].next_sibling) // the sibling is the new cursor
+            });
+        }
+
+        count
     }
 
-    /// Prepends a new child to this node, before existing children.
-    ///
-    /// # Panics
-    ///
-    /// Panics if:
+    /// Removes the subtrees below the given depth, relative to this node, so
+    /// that the nodes at exactly `max_depth` become leaves.
     ///
-    /// * the given new child is `self`, or
-    /// * the given new child is an ancestor of `self`, or
-    /// * the current node or the given new child was already [`remove`]d.
-    ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// Returns the IDs of the roots of the removed subtrees, i.e. the former
+    /// nodes at depth `max_depth + 1`, in pre-order.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// let n1_1 = arena.new_node("1_1");
-    /// n1.prepend(n1_1, &mut arena);
-    /// let n1_2 = arena.new_node("1_2");
-    /// n1.prepend(n1_2, &mut arena);
-    /// let n1_3 = arena.new_node("1_3");
-    /// n1.prepend(n1_3, &mut arena);
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_1_1_1 = arena.new_node("1_1_1_1");
+    /// # n1_1_1.append(n1_1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1 *
+    /// //     |       `-- 1_1_1_1
+    /// //     `-- 1_2
+    ///
+    /// assert_eq!(n1.truncate_depth(&mut arena, 1), &[n1_1_1]);
     ///
     /// // arena
     /// // `-- 1
-    /// //     |-- 1_3
-    /// //     |-- 1_2
-    /// //     `-- 1_1
+    /// //     |-- 1_1
+    /// //     `-- 1_2
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_3));
-    /// assert_eq!(iter.next(), Some(n1_2));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), None);
+    /// assert!(n1_1_1.is_removed(&arena));
+    /// assert!(n1_1_1_1.is_removed(&arena));
+    /// assert_eq!(n1.descendants(&arena).count(), 3);
     /// ```
-    ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn prepend<T>(self, new_child: NodeId, arena: &mut Arena<T>) {
-        self.checked_prepend(new_child, arena)
-            .expect("Preconditions not met: invalid argument");
+    pub fn truncate_depth<T>(self, arena: &mut Arena<T>, max_depth: usize) -> Vec<NodeId> {
+        let removed: Vec<_> = self.nodes_at_depth(arena, max_depth + 1).collect();
+        for &id in &removed {
+            id.remove_subtree(arena);
+        }
+        removed
     }
 
-    /// Prepends a new child to this node, before existing children.
-    ///
-    /// # Failures
-    ///
-    /// * Returns [`NodeError::PrependSelf`] error if the given new child is
-    ///   `self`.
-    /// * Returns [`NodeError::PrependAncestor`] error if the given new child is
-    ///   an ancestor of `self`.
-    /// * Returns [`NodeError::Removed`] error if the given new child or `self`
-    ///   is [`remove`]d.
+    /// Removes all descendants of this node and replaces its data, collapsing
+    /// the subtree into a single node.
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// Returns the ID of this node, which is now a leaf.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// assert!(n1.checked_prepend(n1, &mut arena).is_err());
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1 *
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
     ///
-    /// let n1_1 = arena.new_node("1_1");
-    /// assert!(n1.checked_prepend(n1_1, &mut arena).is_ok());
-    /// ```
+    /// let collapsed = n1_1.collapse_into("1_1 + 1_1_1", &mut arena);
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`NodeError::PrependSelf`]: enum.NodeError.html#variant.PrependSelf
-    /// [`NodeError::Removed`]: enum.NodeError.html#variant.Removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn checked_prepend<T>(
-        self,
-        new_child: NodeId,
-        arena: &mut Arena<T>,
-    ) -> Result<(), NodeError> {
-        if new_child == self {
-            return Err(NodeError::PrependSelf);
-        }
-        if arena[self].is_removed() || arena[new_child].is_removed() {
-            return Err(NodeError::Removed);
-        }
-        if self.ancestors(arena).any(|ancestor| new_child == ancestor) {
-            return Err(NodeError::PrependAncestor);
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1 + 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// assert_eq!(collapsed, n1_1);
+    /// assert_eq!(*arena[n1_1].get(), "1_1 + 1_1_1");
+    /// assert!(n1_1_1.is_removed(&arena));
+    /// assert_eq!(n1.descendants(&arena).count(), 3);
+    /// ```
+    pub fn collapse_into<T>(self, data: T, arena: &mut Arena<T>) -> NodeId {
+        while let Some(child) = arena[self].first_child {
+            child.remove_subtree(arena);
         }
-        insert_with_neighbors(arena, new_child, Some(self), None, arena[self].first_child)
-            .expect("Should never fail: `new_child` is not `self` and they are not removed");
-
-        Ok(())
+        *arena[self].get_mut() = data;
+        self
     }
 
-    /// Inserts a new sibling after this node.
-    ///
-    /// # Panics
-    ///
-    /// Panics if:
+    /// Moves this node and its descendants into a new arena.
     ///
-    /// * the given new sibling is `self`, or
-    /// * the current node or the given new sibling was already [`remove`]d.
+    /// The node is detached first, then the data of every node of the subtree
+    /// is moved (not cloned) into a freshly built arena with the same
+    /// structure. The nodes of the subtree are removed from the source arena.
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// Returns the new arena and the ID of the subtree root within it.
     ///
     /// # Examples
     ///
@@ -860,197 +3390,390 @@ impl NodeId {
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
     /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
     /// #
     /// // arena
     /// // `-- 1
     /// //     |-- 1_1 *
+    /// //     |   `-- 1_1_1
     /// //     `-- 1_2
     ///
-    /// let n1_3 = arena.new_node("1_3");
-    /// n1_1.insert_after(n1_3, &mut arena);
+    /// let (new_arena, root) = n1_1.move_into_new_arena(&mut arena);
     ///
     /// // arena
     /// // `-- 1
-    /// //     |-- 1_1
-    /// //     |-- 1_3 *
     /// //     `-- 1_2
+    /// //
+    /// // new_arena
+    /// // `-- 1_1
+    /// //     `-- 1_1_1
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_3));
-    /// assert_eq!(iter.next(), Some(n1_2));
-    /// assert_eq!(iter.next(), None);
-    /// ```
+    /// assert!(n1_1.is_removed(&arena));
+    /// assert!(n1_1_1.is_removed(&arena));
+    /// assert_eq!(n1.descendants(&arena).count(), 2);
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn insert_after<T>(self, new_sibling: NodeId, arena: &mut Arena<T>) {
-        self.checked_insert_after(new_sibling, arena)
-            .expect("Preconditions not met: invalid argument");
+    /// let data: Vec<_> = root.descendants(&new_arena).map(|id| new_arena[id].get()).collect();
+    /// assert_eq!(data, &[&"1_1", &"1_1_1"]);
+    /// ```
+    pub fn move_into_new_arena<T>(self, arena: &mut Arena<T>) -> (Arena<T>, NodeId) {
+        arena.record(Op::RemoveSubtree(self));
+        self.detach_unrecorded(arena);
+
+        let edges: Vec<_> = self.traverse(arena).collect();
+        let mut new_arena = Arena::with_capacity(edges.len() / 2);
+        let mut new_root = None;
+        let mut parents = Vec::new();
+        for edge in edges {
+            match edge {
+                NodeEdge::Start(id) => {
+                    let new_id = new_arena.new_node(arena.take_node(id));
+                    match parents.last() {
+                        Some(&parent) => insert_last_unchecked(&mut new_arena, new_id, parent),
+                        None => new_root = Some(new_id),
+                    }
+                    parents.push(new_id);
+                }
+                NodeEdge::End(_) => {
+                    parents.pop();
+                }
+            }
+        }
+
+        let new_root = new_root.expect("the subtree should contain at least its root");
+        (new_arena, new_root)
     }
 
-    /// Inserts a new sibling after this node.
+    /// Counts the nodes of the subtree of this node (including the node
+    /// itself) whose data satisfies the given predicate.
     ///
-    /// # Failures
+    /// # Examples
     ///
-    /// * Returns [`NodeError::InsertAfterSelf`] error if the given new sibling
-    ///   is `self`.
-    /// * Returns [`NodeError::Removed`] error if the given new sibling or
-    ///   `self` is [`remove`]d.
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node(1);
+    /// let n1_1 = n1.append_value(2, &mut arena);
+    /// n1_1.append_value(3, &mut arena);
+    /// n1.append_value(4, &mut arena);
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// assert_eq!(n1.count_descendants_matching(&arena, |data| data % 2 == 0), 2);
+    /// assert_eq!(n1_1.count_descendants_matching(&arena, |data| *data > 1), 2);
+    /// ```
+    pub fn count_descendants_matching<T, F>(self, arena: &Arena<T>, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.descendants(arena)
+            .filter(|&id| pred(arena[id].get()))
+            .count()
+    }
+
+    /// Returns `true` if a node of the subtree of this node (including the
+    /// node itself) holds the given value.
+    ///
+    /// The search stops at the first match.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
     /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// assert!(n1.checked_insert_after(n1, &mut arena).is_err());
+    /// let n1 = arena.new_node(1);
+    /// let n1_1 = n1.append_value(2, &mut arena);
+    /// n1_1.append_value(3, &mut arena);
+    /// n1.append_value(4, &mut arena);
+    ///
+    /// assert!(n1.contains_value(&arena, &3));
+    /// assert!(n1_1.contains_value(&arena, &2));
+    /// assert!(!n1_1.contains_value(&arena, &4));
+    /// ```
+    pub fn contains_value<T: PartialEq>(self, arena: &Arena<T>, value: &T) -> bool {
+        self.descendants(arena).any(|id| arena[id].get() == value)
+    }
+
+    /// Returns the number of leaves of the subtree of this node, i.e. of
+    /// descendants without children, which is also the number of distinct
+    /// paths from this node down to a leaf.
+    ///
+    /// A node without children counts as a single leaf.
+    ///
+    /// # Examples
     ///
-    /// let n2 = arena.new_node("2");
-    /// assert!(n1.checked_insert_after(n2, &mut arena).is_ok());
     /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1 *
+    /// //     `-- 1_2 *
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`NodeError::InsertAfterSelf`]: enum.NodeError.html#variant.InsertAfterSelf
-    /// [`NodeError::Removed`]: enum.NodeError.html#variant.Removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn checked_insert_after<T>(
-        self,
-        new_sibling: NodeId,
-        arena: &mut Arena<T>,
-    ) -> Result<(), NodeError> {
-        if new_sibling == self {
-            return Err(NodeError::InsertAfterSelf);
-        }
-        if arena[self].is_removed() || arena[new_sibling].is_removed() {
-            return Err(NodeError::Removed);
-        }
-        new_sibling.detach(arena);
-        let (next_sibling, parent) = {
-            let current = &arena[self];
-            (current.next_sibling, current.parent)
-        };
-        insert_with_neighbors(arena, new_sibling, parent, Some(self), next_sibling)
-            .expect("Should never fail: `new_sibling` is not `self` and they are not removed");
-
-        Ok(())
+    /// assert_eq!(n1.leaf_count(&arena), 2);
+    /// assert_eq!(n1_1.leaf_count(&arena), 1);
+    /// assert_eq!(n1_2.leaf_count(&arena), 1);
+    /// ```
+    pub fn leaf_count<T>(self, arena: &Arena<T>) -> usize {
+        self.descendants(arena)
+            .filter(|&id| arena[id].first_child.is_none())
+            .count()
     }
 
-    /// Inserts a new sibling before this node.
+    /// Returns the first leaf of the subtree of this node, following the first
+    /// children down from this node.
     ///
-    /// # Panics
+    /// Returns the node itself if it has no children.
     ///
-    /// Panics if:
+    /// # Examples
     ///
-    /// * the given new sibling is `self`, or
-    /// * the current node or the given new sibling was already [`remove`]d.
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1 *
+    /// //     `-- 1_2
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// assert_eq!(n1.first_leaf(&arena), n1_1_1);
+    /// assert_eq!(n1_2.first_leaf(&arena), n1_2);
+    /// ```
+    pub fn first_leaf<T>(self, arena: &Arena<T>) -> NodeId {
+        let mut leaf = self;
+        while let Some(first_child) = arena[leaf].first_child {
+            leaf = first_child;
+        }
+        leaf
+    }
+
+    /// Returns the last leaf of the subtree of this node, following the last
+    /// children down from this node.
+    ///
+    /// Returns the node itself if it has no children.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// let n1_1 = arena.new_node("1_1");
-    /// n1.append(n1_1, &mut arena);
-    /// let n1_2 = arena.new_node("1_2");
-    /// n1.append(n1_2, &mut arena);
-    ///
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
     /// // arena
     /// // `-- 1
     /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
     /// //     `-- 1_2 *
     ///
-    /// let n1_3 = arena.new_node("1_3");
-    /// n1_2.insert_before(n1_3, &mut arena);
+    /// assert_eq!(n1.last_leaf(&arena), n1_2);
+    /// assert_eq!(n1_1.last_leaf(&arena), n1_1_1);
+    /// ```
+    pub fn last_leaf<T>(self, arena: &Arena<T>) -> NodeId {
+        let mut leaf = self;
+        while let Some(last_child) = arena[leaf].last_child {
+            leaf = last_child;
+        }
+        leaf
+    }
+
+    /// Returns a node at the maximum depth within the subtree of this node.
+    ///
+    /// If multiple nodes are at the maximum depth, the first one in pre-order
+    /// is returned. Returns the node itself if it has no children.
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n1_2_1 = arena.new_node("1_2_1");
+    /// # n1_2.append(n1_2_1, &mut arena);
+    /// # let n1_2_2 = arena.new_node("1_2_2");
+    /// # n1_2.append(n1_2_2, &mut arena);
+    /// #
     /// // arena
     /// // `-- 1
     /// //     |-- 1_1
-    /// //     |-- 1_3 *
     /// //     `-- 1_2
+    /// //         |-- 1_2_1 *
+    /// //         `-- 1_2_2
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_3));
-    /// assert_eq!(iter.next(), Some(n1_2));
-    /// assert_eq!(iter.next(), None);
+    /// assert_eq!(n1.deepest_descendant(&arena), n1_2_1);
+    /// assert_eq!(n1_1.deepest_descendant(&arena), n1_1);
     /// ```
-    ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn insert_before<T>(self, new_sibling: NodeId, arena: &mut Arena<T>) {
-        self.checked_insert_before(new_sibling, arena)
-            .expect("Preconditions not met: invalid argument");
+    pub fn deepest_descendant<T>(self, arena: &Arena<T>) -> NodeId {
+        let mut deepest = (self, 0);
+        let mut depth = 0;
+        for edge in self.traverse(arena) {
+            match edge {
+                NodeEdge::Start(id) => {
+                    if depth > deepest.1 {
+                        deepest = (id, depth);
+                    }
+                    depth += 1;
+                }
+                NodeEdge::End(_) => depth -= 1,
+            }
+        }
+
+        deepest.0
     }
 
-    /// Inserts a new sibling before this node.
+    /// Returns the diameter of the subtree of this node: the number of edges
+    /// of the longest path between two of its nodes.
     ///
-    /// # Failures
+    /// The diameter is 0 for a single node.
     ///
-    /// * Returns [`NodeError::InsertBeforeSelf`] error if the given new sibling
-    ///   is `self`.
-    /// * Returns [`NodeError::Removed`] error if the given new sibling or
-    ///   `self` is [`remove`]d.
+    /// # Examples
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_1_1_1 = arena.new_node("1_1_1_1");
+    /// # n1_1_1.append(n1_1_1_1, &mut arena);
+    /// # let n1_1_2 = arena.new_node("1_1_2");
+    /// # n1_1.append(n1_1_2, &mut arena);
+    /// # let n1_1_2_1 = arena.new_node("1_1_2_1");
+    /// # n1_1_2.append(n1_1_2_1, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     `-- 1_1
+    /// //         |-- 1_1_1
+    /// //         |   `-- 1_1_1_1 *
+    /// //         `-- 1_1_2
+    /// //             `-- 1_1_2_1 *
+    ///
+    /// // The longest path does not go through `1`.
+    /// assert_eq!(n1.diameter(&arena), 4);
+    /// assert_eq!(n1_1_2.diameter(&arena), 1);
+    /// assert_eq!(n1_1_2_1.diameter(&arena), 0);
+    /// ```
+    pub fn diameter<T>(self, arena: &Arena<T>) -> usize {
+        // The two longest downward paths through distinct children of each
+        // node, so that the first one is the height of the node.
+        let mut deepest = vec![(0, 0); arena.count()];
+        let mut diameter = 0;
+        for edge in self.traverse(arena) {
+            let NodeEdge::End(id) = edge else {
+                continue;
+            };
+            let (first, second) = deepest[id.index0()];
+            diameter = diameter.max(first + second);
+            if id == self {
+                break;
+            }
+            if let Some(parent) = arena[id].parent {
+                let branch = first + 1;
+                let (first, second) = &mut deepest[parent.index0()];
+                if branch > *first {
+                    *second = *first;
+                    *first = branch;
+                } else if branch > *second {
+                    *second = branch;
+                }
+            }
+        }
+        diameter
+    }
+
+    /// Returns the centroid of the subtree of this node: the node whose
+    /// removal minimizes the size of the largest remaining component.
+    ///
+    /// No child subtree of the centroid, nor the rest of the tree above it,
+    /// holds more than half of the nodes. When two nodes qualify, the one
+    /// closer to this node is returned.
     ///
     /// # Examples
     ///
     /// ```
     /// # use indextree::Arena;
-    /// let mut arena = Arena::new();
-    /// let n1 = arena.new_node("1");
-    /// assert!(n1.checked_insert_before(n1, &mut arena).is_err());
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_1_2 = arena.new_node("1_1_2");
+    /// # n1_1.append(n1_1_2, &mut arena);
+    /// # let n1_1_3 = arena.new_node("1_1_3");
+    /// # n1_1.append(n1_1_3, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     `-- 1_1 *
+    /// //         |-- 1_1_1
+    /// //         |-- 1_1_2
+    /// //         `-- 1_1_3
     ///
-    /// let n2 = arena.new_node("2");
-    /// assert!(n1.checked_insert_before(n2, &mut arena).is_ok());
+    /// assert_eq!(n1.centroid(&arena), n1_1);
+    /// assert_eq!(n1_1_1.centroid(&arena), n1_1_1);
     /// ```
-    ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    /// [`NodeError::InsertBeforeSelf`]: enum.NodeError.html#variant.InsertBeforeSelf
-    /// [`NodeError::Removed`]: enum.NodeError.html#variant.Removed
-    /// [`remove`]: struct.NodeId.html#method.remove
-    pub fn checked_insert_before<T>(
-        self,
-        new_sibling: NodeId,
-        arena: &mut Arena<T>,
-    ) -> Result<(), NodeError> {
-        if new_sibling == self {
-            return Err(NodeError::InsertBeforeSelf);
+    pub fn centroid<T>(self, arena: &Arena<T>) -> NodeId {
+        let descendants: Vec<_> = self.descendants(arena).collect();
+        let mut sizes = vec![0; arena.count()];
+        for &id in descendants.iter().rev() {
+            sizes[id.index0()] += 1;
+            if id != self {
+                if let Some(parent) = arena[id].parent {
+                    sizes[parent.index0()] += sizes[id.index0()];
+                }
+            }
         }
-        if arena[self].is_removed() || arena[new_sibling].is_removed() {
-            return Err(NodeError::Removed);
-        }
-        new_sibling.detach(arena);
-        let (previous_sibling, parent) = {
-            let current = &arena[self];
-            (current.previous_sibling, current.parent)
-        };
-        insert_with_neighbors(arena, new_sibling, parent, previous_sibling, Some(self))
-            .expect("Should never fail: `new_sibling` is not `self` and they are not removed");
 
-        Ok(())
+        let total = descendants.len();
+        let mut centroid = self;
+        while let Some(heavy) = centroid
+            .children(arena)
+            .find(|child| 2 * sizes[child.index0()] > total)
+        {
+            centroid = heavy;
+        }
+        centroid
     }
 
-    /// Removes a node from the arena.
+    /// Returns the position of this node in the pre-order traversal of the
+    /// whole forest, as given by [`Arena::iter_tree_order`].
     ///
-    /// Children of the removed node will be inserted to the place where the
-    /// removed node was.
+    /// This walks the forest from the first root and is therefore O(n) in the
+    /// number of nodes preceding this one.
     ///
-    /// Please note that the node will not be removed from the internal arena
-    /// storage, but marked as `removed`. Traversing the arena returns a
-    /// plain iterator and contains removed elements too.
+    /// # Panics
     ///
-    /// To check if the node is removed or not, use [`Node::is_removed()`].
+    /// Panics if the node is removed.
     ///
     /// # Examples
     ///
@@ -1060,83 +3783,94 @@ impl NodeId {
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
     /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_2_1 = arena.new_node("1_2_1");
-    /// # n1_2.append(n1_2_1, &mut arena);
-    /// # let n1_2_2 = arena.new_node("1_2_2");
-    /// # n1_2.append(n1_2_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
+    /// # let n2 = arena.new_node("2");
     /// #
     /// // arena
-    /// // `-- 1
-    /// //     |-- 1_1
-    /// //     |-- 1_2 *
-    /// //     |   |-- 1_2_1
-    /// //     |   `-- 1_2_2
-    /// //     `-- 1_3
+    /// // |-- 1                                                // #0
+    /// // |   |-- 1_1                                          // #1
+    /// // |   |   `-- 1_1_1                                    // #2
+    /// // |   `-- 1_2                                          // #3
+    /// // `-- 2                                                // #4
+    ///
+    /// assert_eq!(n1.preorder_index(&arena), 0);
+    /// assert_eq!(n1_1.preorder_index(&arena), 1);
+    /// assert_eq!(n1_2.preorder_index(&arena), 3);
+    /// assert_eq!(n2.preorder_index(&arena), 4);
+    /// ```
     ///
-    /// n1_2.remove(&mut arena);
+    /// [`Arena::iter_tree_order`]: struct.Arena.html#method.iter_tree_order
+    pub fn preorder_index<T>(self, arena: &Arena<T>) -> usize {
+        arena
+            .iter_tree_order()
+            .position(|id| id == self)
+            .expect("the node should not be removed")
+    }
+
+    /// Returns the positions of the `Start` and `End` edges of this node in
+    /// the traversal of the whole forest.
     ///
-    /// // arena
-    /// // `-- 1
-    /// //     |-- 1_1
-    /// //     |-- 1_2_1
-    /// //     |-- 1_2_2
-    /// //     `-- 1_3
+    /// Every tree of the forest is traversed as [`NodeId::traverse`], in the
+    /// order of [`Arena::roots`], and each edge takes one position. The
+    /// range of a node contains the ranges of all of its descendants, so
+    /// range containment and intersection reduce to numeric comparisons.
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_2_1));
-    /// assert_eq!(iter.next(), Some(n1_2_2));
-    /// assert_eq!(iter.next(), Some(n1_3));
-    /// assert_eq!(iter.next(), None);
+    /// # Panics
+    ///
+    /// Panics if the node is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// # let n2 = arena.new_node("2");
+    /// #
+    /// // arena
+    /// // |-- 1                                                // #0, #5
+    /// // |   |-- 1_1                                          // #1, #2
+    /// // |   `-- 1_2                                          // #3, #4
+    /// // `-- 2                                                // #6, #7
+    ///
+    /// assert_eq!(n1.range(&arena), (0, 5));
+    /// assert_eq!(n1_1.range(&arena), (1, 2));
+    /// assert_eq!(n1_2.range(&arena), (3, 4));
+    /// assert_eq!(n2.range(&arena), (6, 7));
     /// ```
     ///
-    /// [`Node::is_removed()`]: struct.Node.html#method.is_removed
-    pub fn remove<T>(self, arena: &mut Arena<T>) {
-        debug_assert_triangle_nodes!(
-            arena,
-            arena[self].parent,
-            arena[self].previous_sibling,
-            Some(self)
-        );
-        debug_assert_triangle_nodes!(
-            arena,
-            arena[self].parent,
-            Some(self),
-            arena[self].next_sibling
-        );
-        debug_assert_triangle_nodes!(arena, Some(self), None, arena[self].first_child);
-        debug_assert_triangle_nodes!(arena, Some(self), arena[self].last_child, None);
-
-        // Retrieve needed values.
-        let (parent, previous_sibling, next_sibling, first_child, last_child) = {
-            let node = &arena[self];
-            (
-                node.parent,
-                node.previous_sibling,
-                node.next_sibling,
-                node.first_child,
-                node.last_child,
-            )
-        };
-
-        assert_eq!(first_child.is_some(), last_child.is_some());
-        self.detach(arena);
-        if let (Some(first_child), Some(last_child)) = (first_child, last_child) {
-            let range = SiblingsRange::new(first_child, last_child).detach_from_siblings(arena);
-            range
-                .transplant(arena, parent, previous_sibling, next_sibling)
-                .expect("Should never fail: neighbors and children must be consistent");
+    /// [`NodeId::traverse`]: struct.NodeId.html#method.traverse
+    /// [`Arena::roots`]: struct.Arena.html#method.roots
+    pub fn range<T>(self, arena: &Arena<T>) -> (usize, usize) {
+        let mut start = None;
+        let edges = arena.roots().flat_map(|root| root.traverse(arena));
+        for (position, edge) in edges.enumerate() {
+            match edge {
+                NodeEdge::Start(id) if id == self => start = Some(position),
+                NodeEdge::End(id) if id == self => {
+                    return (start.expect("start edge precedes end edge"), position)
+                }
+                _ => {}
+            }
         }
-        arena.free_node(self);
-        debug_assert!(arena[self].is_detached());
+        panic!("the node should not be removed")
     }
 
-    /// Removes a node and its descendants from the arena.
+    /// Returns whether the subtree of this node is height-balanced.
+    ///
+    /// The subtree is balanced if, for every node in it, the heights of the
+    /// subtrees of its children differ by at most 1 (AVL-style). A missing
+    /// child counts as an empty subtree of height -1, so a node with a single
+    /// child is only balanced if that child is a leaf, and a chain of more
+    /// than two nodes is not balanced.
+    ///
     /// # Examples
     ///
     /// ```
@@ -1145,51 +3879,199 @@ impl NodeId {
     /// # let n1 = arena.new_node("1");
     /// # let n1_1 = arena.new_node("1_1");
     /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
     /// # let n1_2 = arena.new_node("1_2");
     /// # n1.append(n1_2, &mut arena);
-    /// # let n1_2_1 = arena.new_node("1_2_1");
-    /// # n1_2.append(n1_2_1, &mut arena);
-    /// # let n1_2_2 = arena.new_node("1_2_2");
-    /// # n1_2.append(n1_2_2, &mut arena);
-    /// # let n1_3 = arena.new_node("1_3");
-    /// # n1.append(n1_3, &mut arena);
     /// #
     /// // arena
     /// // `-- 1
     /// //     |-- 1_1
-    /// //     |-- 1_2 *
-    /// //     |   |-- 1_2_1
-    /// //     |   `-- 1_2_2
-    /// //     `-- 1_3
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
     ///
-    /// n1_2.remove_subtree(&mut arena);
+    /// assert!(n1.is_balanced(&arena));
+    ///
+    /// let n1_1_1_1 = arena.new_node("1_1_1_1");
+    /// n1_1_1.append(n1_1_1_1, &mut arena);
+    /// assert!(!n1.is_balanced(&arena));
+    /// assert!(!n1_1.is_balanced(&arena));
+    /// assert!(n1_1_1.is_balanced(&arena));
+    /// ```
+    pub fn is_balanced<T>(self, arena: &Arena<T>) -> bool {
+        let mut stack: Vec<ChildHeights> = Vec::new();
+        for edge in self.traverse(arena) {
+            match edge {
+                NodeEdge::Start(_) => stack.push(ChildHeights::default()),
+                NodeEdge::End(_) => {
+                    let heights = stack.pop().expect("each end should match a start");
+                    if !heights.is_balanced() {
+                        return false;
+                    }
+                    if let Some(parent) = stack.last_mut() {
+                        parent.push(heights.height());
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Folds the subtree of this node bottom-up, combining the data of each
+    /// node with the results of its children.
+    ///
+    /// `combine` is called once per node in post-order, with the node data and
+    /// the results of its children in order. The result for this node is
+    /// returned.
+    ///
+    /// # Examples
     ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node(1);
+    /// # let n1_1 = arena.new_node(2);
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node(3);
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node(4);
+    /// # n1.append(n1_2, &mut arena);
+    /// #
     /// // arena
     /// // `-- 1
-    /// //     |-- 1_1
-    /// //     `-- 1_3
+    /// //     |-- 2
+    /// //     |   `-- 3
+    /// //     `-- 4
+    ///
+    /// let sum = n1.accumulate_subtree(&arena, |data, children: &[i32]| {
+    ///     data + children.iter().sum::<i32>()
+    /// });
+    /// assert_eq!(sum, 10);
+    ///
+    /// let height = n1.accumulate_subtree(&arena, |_, children: &[usize]| {
+    ///     children.iter().max().map_or(0, |height| height + 1)
+    /// });
+    /// assert_eq!(height, 2);
+    /// ```
+    pub fn accumulate_subtree<T, B, F>(self, arena: &Arena<T>, combine: F) -> B
+    where
+        F: Fn(&T, &[B]) -> B,
+    {
+        let mut stack: Vec<Vec<B>> = Vec::new();
+        for edge in self.traverse(arena) {
+            match edge {
+                NodeEdge::Start(_) => stack.push(Vec::new()),
+                NodeEdge::End(id) => {
+                    let children = stack.pop().expect("each end should match a start");
+                    let result = combine(arena[id].get(), &children);
+                    match stack.last_mut() {
+                        Some(parent) => parent.push(result),
+                        None => return result,
+                    }
+                }
+            }
+        }
+
+        unreachable!("the traversal should end with this node")
+    }
+
+    /// Returns whether the subtrees of this node and `other` are equal, both
+    /// in structure and in data.
+    ///
+    /// The subtrees are compared breadth-first, and the number of children of
+    /// each pair of nodes is compared before their data and before descending
+    /// into them. The comparison stops at the first mismatch, so subtrees
+    /// that differ near the top are rejected without visiting the rest.
+    ///
+    /// # Examples
     ///
-    /// let mut iter = n1.descendants(&arena);
-    /// assert_eq!(iter.next(), Some(n1));
-    /// assert_eq!(iter.next(), Some(n1_1));
-    /// assert_eq!(iter.next(), Some(n1_3));
-    /// assert_eq!(iter.next(), None);
     /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node("a");
+    /// n1.append_value("b", &mut arena);
+    /// let n2 = arena.new_node("a");
+    /// let n2_1 = n2.append_value("b", &mut arena);
     ///
-    pub fn remove_subtree<T>(self, arena: &mut Arena<T>) {
-        self.detach(arena);
+    /// assert!(n1.subtree_eq_fast(n2, &arena));
+    ///
+    /// n2_1.append_value("c", &mut arena);
+    /// assert!(!n1.subtree_eq_fast(n2, &arena));
+    /// ```
+    pub fn subtree_eq_fast<T: PartialEq>(self, other: NodeId, arena: &Arena<T>) -> bool {
+        let mut queue = VecDeque::new();
+        queue.push_back((self, other));
+        while let Some((a, b)) = queue.pop_front() {
+            if a.children(arena).count() != b.children(arena).count()
+                || arena[a].get() != arena[b].get()
+            {
+                return false;
+            }
+            queue.extend(a.children(arena).zip(b.children(arena)));
+        }
 
-        // use a preorder traversal to remove node.
-        let mut cursor = Some(self);
-        while let Some(id) = cursor {
-            arena.free_node(id);
-            let node = &arena[id];
-            cursor = node.first_child.or(node.next_sibling).or_else(|| {
-                id.ancestors(arena) // traverse ancestors upwards
-                    .skip(1) // skip the starting node itself
-                    .find(|n| arena[*n].next_sibling.is_some()) // first ancestor with a sibling
-                    .and_then(|n| arena[n].next_sibling) // the sibling is the new cursor
-            });
+        true
+    }
+
+    /// Swaps the data of this node with the data of its parent, keeping the
+    /// tree structure untouched.
+    ///
+    /// Returns `false` (and does nothing) if the node has no parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node(1);
+    /// let n1_1 = n1.append_value(2, &mut arena);
+    ///
+    /// assert!(n1_1.swap_data_with_parent(&mut arena));
+    /// assert_eq!(*arena[n1].get(), 2);
+    /// assert_eq!(*arena[n1_1].get(), 1);
+    /// assert_eq!(arena[n1_1].parent(), Some(n1));
+    ///
+    /// assert!(!n1.swap_data_with_parent(&mut arena));
+    /// ```
+    pub fn swap_data_with_parent<T>(self, arena: &mut Arena<T>) -> bool {
+        match arena[self].parent {
+            Some(parent) => parent.swap_data_with_child(self, arena),
+            None => false,
+        }
+    }
+
+    /// Swaps the data of this node with the data of the given child, keeping
+    /// the tree structure untouched.
+    ///
+    /// Returns `false` (and does nothing) if `child` is not a child of this
+    /// node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let n1 = arena.new_node(1);
+    /// let n1_1 = n1.append_value(2, &mut arena);
+    /// let n1_1_1 = n1_1.append_value(3, &mut arena);
+    ///
+    /// assert!(n1.swap_data_with_child(n1_1, &mut arena));
+    /// assert_eq!(*arena[n1].get(), 2);
+    /// assert_eq!(*arena[n1_1].get(), 1);
+    ///
+    /// assert!(!n1.swap_data_with_child(n1_1_1, &mut arena));
+    /// ```
+    pub fn swap_data_with_child<T>(self, child: NodeId, arena: &mut Arena<T>) -> bool {
+        if arena[child].parent != Some(self) {
+            return false;
+        }
+        match arena.get_disjoint_mut([self, child]) {
+            Some([node, child]) => {
+                core::mem::swap(node.get_mut(), child.get_mut());
+                true
+            }
+            None => false,
         }
     }
 
@@ -1294,6 +4176,181 @@ impl NodeId {
     pub fn debug_pretty_print<'a, T>(&'a self, arena: &'a Arena<T>) -> DebugPrettyPrint<'a, T> {
         DebugPrettyPrint::new(self, arena)
     }
+
+    /// Returns the pretty-printable proxy object to the node and descendants,
+    /// rendering each node data with the given closure.
+    ///
+    /// This does not require `T` to implement [`Display`] or [`Debug`].
+    ///
+    /// # (No) guarantees
+    ///
+    /// This is provided mainly for debugging purpose. Note that the output
+    /// format is not guaranteed to be stable, and any format changes won't be
+    /// considered as breaking changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// struct Opaque {
+    ///     name: &'static str,
+    /// }
+    ///
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node(Opaque { name: "root" });
+    /// let n0 = root.append_value(Opaque { name: "0" }, &mut arena);
+    /// n0.append_value(Opaque { name: "0_0" }, &mut arena);
+    /// root.append_value(Opaque { name: "1" }, &mut arena);
+    ///
+    /// let printable = root.pretty_print_with(&arena, |data, f| f.write_str(data.name));
+    ///
+    /// let expected = r#"root
+    /// |-- 0
+    /// |   `-- 0_0
+    /// `-- 1"#;
+    /// assert_eq!(printable.to_string(), expected);
+    /// ```
+    ///
+    /// [`Display`]: https://doc.rust-lang.org/stable/std/fmt/trait.Display.html
+    /// [`Debug`]: https://doc.rust-lang.org/stable/std/fmt/trait.Debug.html
+    #[inline]
+    #[must_use]
+    pub fn pretty_print_with<T, F>(self, arena: &Arena<T>, f: F) -> PrettyPrintWith<'_, T, F>
+    where
+        F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    {
+        PrettyPrintWith::new(self, arena, f)
+    }
+
+    /// Renders the (sub)tree of this node as a string, using the `Display`
+    /// implementation of the node data.
+    ///
+    /// This is a shortcut for `format!("{}", id.debug_pretty_print(&arena))`;
+    /// see [`debug_pretty_print`] for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("root");
+    /// root.append_value("child", &mut arena);
+    ///
+    /// assert_eq!(root.to_tree_string(&arena), "root\n`-- child");
+    /// ```
+    ///
+    /// [`debug_pretty_print`]: struct.NodeId.html#method.debug_pretty_print
+    #[cfg(feature = "std")]
+    pub fn to_tree_string<T: fmt::Display>(self, arena: &Arena<T>) -> String {
+        self.debug_pretty_print(arena).to_string()
+    }
+
+    /// Renders the (sub)tree of this node as a string, using the `Debug`
+    /// implementation of the node data.
+    ///
+    /// This is a shortcut for `format!("{:?}", id.debug_pretty_print(&arena))`;
+    /// see [`debug_pretty_print`] for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("root");
+    /// root.append_value("child", &mut arena);
+    ///
+    /// assert_eq!(root.to_tree_string_debug(&arena), "\"root\"\n`-- \"child\"");
+    /// ```
+    ///
+    /// [`debug_pretty_print`]: struct.NodeId.html#method.debug_pretty_print
+    #[cfg(feature = "std")]
+    pub fn to_tree_string_debug<T: fmt::Debug>(self, arena: &Arena<T>) -> String {
+        format!("{:?}", self.debug_pretty_print(arena))
+    }
+
+    /// Returns an iterator of the lines of the (sub)tree of this node as
+    /// rendered by [`to_tree_string`], one per node.
+    ///
+    /// The lines are formatted lazily, so large trees can be logged or paged
+    /// without building the whole string. Data rendered over several lines
+    /// yields a single item containing line breaks, and a trailing line break
+    /// is dropped. Data rendered as an empty string still yields its branch,
+    /// e.g. `"|-- "`, whereas [`to_tree_string`] leaves such nodes out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("root");
+    /// let child = root.append_value("child", &mut arena);
+    /// child.append_value("grandchild", &mut arena);
+    /// root.append_value("other", &mut arena);
+    ///
+    /// let mut lines = root.pretty_lines(&arena);
+    /// assert_eq!(lines.next().as_deref(), Some("root"));
+    /// assert_eq!(lines.next().as_deref(), Some("|-- child"));
+    /// assert_eq!(lines.next().as_deref(), Some("|   `-- grandchild"));
+    /// assert_eq!(lines.next().as_deref(), Some("`-- other"));
+    /// assert_eq!(lines.next(), None);
+    /// ```
+    ///
+    /// [`to_tree_string`]: struct.NodeId.html#method.to_tree_string
+    #[cfg(feature = "std")]
+    pub fn pretty_lines<T: fmt::Display>(
+        self,
+        arena: &Arena<T>,
+    ) -> impl Iterator<Item = String> + '_ {
+        pretty_lines(self, arena)
+    }
+
+    /// Renders the (sub)tree of this node as an S-expression, using the
+    /// `Display` implementation of the node data.
+    ///
+    /// Each node is written as `(data children...)`, with the children
+    /// separated by single spaces. Parentheses, backslashes and whitespace in
+    /// the data are escaped with a backslash, so the output can be parsed
+    /// back with [`Arena::from_sexpr`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// let mut arena = Arena::new();
+    /// let root = arena.new_node("root");
+    /// root.append_value("child 1", &mut arena);
+    /// let child2 = root.append_value("child2", &mut arena);
+    /// child2.append_value("(grandchild)", &mut arena);
+    ///
+    /// assert_eq!(
+    ///     root.to_sexpr(&arena),
+    ///     r"(root (child\ 1) (child2 (\(grandchild\))))"
+    /// );
+    /// ```
+    ///
+    /// [`Arena::from_sexpr`]: struct.Arena.html#method.from_sexpr
+    #[cfg(feature = "std")]
+    pub fn to_sexpr<T: fmt::Display>(self, arena: &Arena<T>) -> String {
+        let mut sexpr = String::new();
+        for edge in self.traverse(arena) {
+            match edge {
+                NodeEdge::Start(id) => {
+                    if id != self {
+                        sexpr.push(' ');
+                    }
+                    sexpr.push('(');
+                    for c in arena[id].get().to_string().chars() {
+                        if matches!(c, '(' | ')' | '\\') || c.is_whitespace() {
+                            sexpr.push('\\');
+                        }
+                        sexpr.push(c);
+                    }
+                }
+                NodeEdge::End(_) => sexpr.push(')'),
+            }
+        }
+        sexpr
+    }
 }
 
 #[cfg(test)]
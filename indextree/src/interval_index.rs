@@ -0,0 +1,111 @@
+//! Precomputed subtree intervals.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Arena, NodeEdge, NodeId};
+
+/// Euler-tour interval of a single node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Interval {
+    /// Position of the node start.
+    enter: usize,
+    /// Position of the node end.
+    exit: usize,
+}
+
+/// Precomputed Euler-tour intervals of every node in an [`Arena`].
+///
+/// Each live node is assigned an `[enter, exit]` interval by a depth-first
+/// traversal of the forest, so that a node is a descendant of another exactly
+/// when its interval is contained in the other's. This makes repeated
+/// ancestor queries on a static tree O(1).
+///
+/// The index is a snapshot: it is invalidated by any mutation of the arena
+/// (appending, detaching, removing, ...) and must be recomputed afterwards.
+///
+/// Created by [`Arena::compute_intervals`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::compute_intervals`]: struct.Arena.html#method.compute_intervals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalIndex {
+    /// Intervals indexed by the zero-based node index, `None` for removed
+    /// nodes.
+    intervals: Vec<Option<Interval>>,
+}
+
+impl IntervalIndex {
+    /// Computes the intervals of all nodes in the arena.
+    pub(crate) fn new<T>(arena: &Arena<T>) -> Self {
+        let mut intervals = Vec::with_capacity(arena.count());
+        intervals.resize(arena.count(), None);
+        let mut position = 0;
+        for root in arena.roots() {
+            for edge in root.traverse(arena) {
+                match edge {
+                    NodeEdge::Start(id) => {
+                        intervals[id.index0()] = Some(Interval {
+                            enter: position,
+                            exit: position,
+                        });
+                    }
+                    NodeEdge::End(id) => {
+                        if let Some(interval) = intervals[id.index0()].as_mut() {
+                            interval.exit = position;
+                        }
+                    }
+                }
+                position += 1;
+            }
+        }
+
+        Self { intervals }
+    }
+
+    /// Returns the interval of the given node, if known.
+    fn interval(&self, id: NodeId) -> Option<Interval> {
+        self.intervals.get(id.index0()).copied().flatten()
+    }
+
+    /// Returns `true` if `node` is `ancestor` itself or one of its
+    /// descendants.
+    ///
+    /// Returns `false` if either node was not live when the index was
+    /// computed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use indextree::Arena;
+    /// # let mut arena = Arena::new();
+    /// # let n1 = arena.new_node("1");
+    /// # let n1_1 = arena.new_node("1_1");
+    /// # n1.append(n1_1, &mut arena);
+    /// # let n1_1_1 = arena.new_node("1_1_1");
+    /// # n1_1.append(n1_1_1, &mut arena);
+    /// # let n1_2 = arena.new_node("1_2");
+    /// # n1.append(n1_2, &mut arena);
+    /// #
+    /// // arena
+    /// // `-- 1
+    /// //     |-- 1_1
+    /// //     |   `-- 1_1_1
+    /// //     `-- 1_2
+    ///
+    /// let index = arena.compute_intervals();
+    /// assert!(index.is_descendant(n1, n1_1_1));
+    /// assert!(index.is_descendant(n1_1, n1_1_1));
+    /// assert!(index.is_descendant(n1_2, n1_2));
+    /// assert!(!index.is_descendant(n1_2, n1_1_1));
+    /// assert!(!index.is_descendant(n1_1_1, n1));
+    /// ```
+    pub fn is_descendant(&self, ancestor: NodeId, node: NodeId) -> bool {
+        match (self.interval(ancestor), self.interval(node)) {
+            (Some(ancestor), Some(node)) => {
+                ancestor.enter <= node.enter && node.exit <= ancestor.exit
+            }
+            _ => false,
+        }
+    }
+}
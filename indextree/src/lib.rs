@@ -31,14 +31,25 @@ extern crate alloc;
 #[allow(deprecated)]
 pub use crate::{
     arena::Arena,
-    debug_pretty_print::DebugPrettyPrint,
-    error::NodeError,
+    bitset::NodeBitSet,
+    builder::TreeBuilder,
+    debug_pretty_print::{DebugPrettyPrint, PrettyPrintWith},
+    error::{NodeError, ParseError},
+    frozen::FrozenArena,
     id::NodeId,
+    interval_index::IntervalIndex,
     node::Node,
+    ops::{Op, OpRecorder},
+    removal::RemovalEffect,
+    snapshot::Snapshot,
+    summary::TreeSummary,
     traverse::{
-        Ancestors, Children, Descendants, FollowingSiblings, NodeEdge, PrecedingSiblings,
-        Predecessors, ReverseChildren, ReverseTraverse, Traverse,
+        zip_breadth_first, Ancestors, AncestorsWithDepth, BreadthFirstReverse, Children,
+        Descendants, FollowingSiblings, InOrder, NodeEdge, PrecedingSiblings,
+        PrecedingSiblingsInOrder, Predecessors, ReverseChildren, ReverseTraverse, Traverse,
+        WalkEvent,
     },
+    visitor::TreeVisitor,
 };
 
 #[cfg(feature = "macros")]
@@ -48,9 +59,18 @@ pub use indextree_macros as macros;
 pub(crate) mod relations;
 
 mod arena;
+mod bitset;
+mod builder;
 mod debug_pretty_print;
 pub(crate) mod error;
+mod frozen;
 mod id;
+mod interval_index;
 mod node;
+mod ops;
+mod removal;
 pub(crate) mod siblings_range;
+mod snapshot;
+mod summary;
 mod traverse;
+mod visitor;
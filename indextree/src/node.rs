@@ -18,6 +18,44 @@ pub(crate) enum NodeData<T> {
     Data(T),
     /// The next free node position.
     NextFree(Option<usize>),
+    /// The next free node position, and the data the node had when it was
+    /// removed.
+    #[cfg(feature = "retain-removed-data")]
+    Removed(Option<usize>, T),
+}
+
+impl<T> NodeData<T> {
+    /// Returns the next free node position, or `None` if the node holds
+    /// data.
+    pub(crate) fn next_free(&self) -> Option<Option<usize>> {
+        match *self {
+            NodeData::Data(_) => None,
+            NodeData::NextFree(next_free) => Some(next_free),
+            #[cfg(feature = "retain-removed-data")]
+            NodeData::Removed(next_free, _) => Some(next_free),
+        }
+    }
+
+    /// Sets the next free node position, keeping any retained data.
+    pub(crate) fn set_next_free(&mut self, next: Option<usize>) {
+        #[cfg(feature = "retain-removed-data")]
+        if let NodeData::Removed(next_free, _) = self {
+            *next_free = next;
+            return;
+        }
+        *self = NodeData::NextFree(next);
+    }
+
+    /// Marks the node as free, retaining its data if the
+    /// `retain-removed-data` feature is enabled.
+    pub(crate) fn free(&mut self) {
+        #[cfg(feature = "retain-removed-data")]
+        if let NodeData::Data(data) = core::mem::replace(self, NodeData::NextFree(None)) {
+            *self = NodeData::Removed(None, data);
+            return;
+        }
+        *self = NodeData::NextFree(None);
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -69,10 +107,13 @@ impl<T> Node<T> {
     }
 
     /// Convert a removed `Node` to normal with default state and given data.
-    pub(crate) fn reuse(&mut self, data: T) {
-        debug_assert!(matches!(self.data, NodeData::NextFree(_)));
+    ///
+    /// The new stamp is at least `floor`.
+    pub(crate) fn reuse(&mut self, data: T, floor: NodeStamp) {
+        debug_assert!(self.data.next_free().is_some());
         debug_assert!(self.stamp.is_removed());
         self.stamp.reuse();
+        self.stamp = self.stamp.max(floor);
         self.parent = None;
         self.previous_sibling = None;
         self.next_sibling = None;
@@ -0,0 +1,144 @@
+//! Operation log.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "std")]
+use std::ops::{Deref, DerefMut};
+
+use crate::{Arena, NodeId};
+
+/// A structural change of an [`Arena`], recorded by an [`OpRecorder`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`OpRecorder`]: struct.OpRecorder.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// `child` was appended to `parent` (by [`NodeId::append`],
+    /// [`NodeId::checked_append`], [`NodeId::append_value`] and related
+    /// methods, or [`NodeId::reorder_children`]).
+    ///
+    /// [`NodeId::append`]: struct.NodeId.html#method.append
+    /// [`NodeId::checked_append`]: struct.NodeId.html#method.checked_append
+    /// [`NodeId::append_value`]: struct.NodeId.html#method.append_value
+    /// [`NodeId::reorder_children`]: struct.NodeId.html#method.reorder_children
+    Append {
+        /// The new parent.
+        parent: NodeId,
+        /// The appended node.
+        child: NodeId,
+    },
+    /// `child` was prepended to `parent` (by [`NodeId::prepend`] or
+    /// [`NodeId::checked_prepend`]).
+    ///
+    /// [`NodeId::prepend`]: struct.NodeId.html#method.prepend
+    /// [`NodeId::checked_prepend`]: struct.NodeId.html#method.checked_prepend
+    Prepend {
+        /// The new parent.
+        parent: NodeId,
+        /// The prepended node.
+        child: NodeId,
+    },
+    /// `sibling` was inserted after `node` (by [`NodeId::insert_after`],
+    /// [`NodeId::checked_insert_after`], [`NodeId::insert_between`] or
+    /// [`NodeId::swap_siblings`]).
+    ///
+    /// [`NodeId::insert_after`]: struct.NodeId.html#method.insert_after
+    /// [`NodeId::checked_insert_after`]: struct.NodeId.html#method.checked_insert_after
+    /// [`NodeId::insert_between`]: struct.NodeId.html#method.insert_between
+    /// [`NodeId::swap_siblings`]: struct.NodeId.html#method.swap_siblings
+    InsertAfter {
+        /// The node the sibling was inserted after.
+        node: NodeId,
+        /// The inserted node.
+        sibling: NodeId,
+    },
+    /// `sibling` was inserted before `node` (by [`NodeId::insert_before`],
+    /// [`NodeId::checked_insert_before`] or [`NodeId::swap_siblings`]).
+    ///
+    /// [`NodeId::insert_before`]: struct.NodeId.html#method.insert_before
+    /// [`NodeId::checked_insert_before`]: struct.NodeId.html#method.checked_insert_before
+    /// [`NodeId::swap_siblings`]: struct.NodeId.html#method.swap_siblings
+    InsertBefore {
+        /// The node the sibling was inserted before.
+        node: NodeId,
+        /// The inserted node.
+        sibling: NodeId,
+    },
+    /// The node was detached (by [`NodeId::detach`] or
+    /// [`NodeId::make_root`]).
+    ///
+    /// [`NodeId::detach`]: struct.NodeId.html#method.detach
+    /// [`NodeId::make_root`]: struct.NodeId.html#method.make_root
+    Detach(NodeId),
+    /// The node was removed, its children taking its place (by
+    /// [`NodeId::remove`], or by [`NodeId::remove_splice_at_end`] once the
+    /// moves of its children are recorded).
+    ///
+    /// [`NodeId::remove`]: struct.NodeId.html#method.remove
+    /// [`NodeId::remove_splice_at_end`]: struct.NodeId.html#method.remove_splice_at_end
+    Remove(NodeId),
+    /// The node and its descendants were removed (by
+    /// [`NodeId::remove_subtree`] and related methods, or moved out by
+    /// [`NodeId::move_into_new_arena`]).
+    ///
+    /// [`NodeId::remove_subtree`]: struct.NodeId.html#method.remove_subtree
+    /// [`NodeId::move_into_new_arena`]: struct.NodeId.html#method.move_into_new_arena
+    RemoveSubtree(NodeId),
+}
+
+/// A guard recording the structural changes of an [`Arena`].
+///
+/// The arena is accessible through the recorder, which dereferences to it.
+/// Recording stops when the recorder is dropped.
+///
+/// Created by [`Arena::record_ops`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::record_ops`]: struct.Arena.html#method.record_ops
+#[derive(Debug)]
+pub struct OpRecorder<'a, T> {
+    /// Arena being recorded.
+    arena: &'a mut Arena<T>,
+}
+
+impl<'a, T> OpRecorder<'a, T> {
+    /// Creates a new recorder for the arena.
+    pub(crate) fn new(arena: &'a mut Arena<T>) -> Self {
+        arena.start_recording();
+        Self { arena }
+    }
+
+    /// Returns the operations recorded so far, in order.
+    pub fn ops(&self) -> &[Op] {
+        self.arena.recorded_ops()
+    }
+
+    /// Takes the operations recorded so far, and keeps recording.
+    pub fn take_ops(&mut self) -> Vec<Op> {
+        self.arena.take_recorded_ops()
+    }
+}
+
+impl<T> Deref for OpRecorder<'_, T> {
+    type Target = Arena<T>;
+
+    fn deref(&self) -> &Arena<T> {
+        self.arena
+    }
+}
+
+impl<T> DerefMut for OpRecorder<'_, T> {
+    fn deref_mut(&mut self) -> &mut Arena<T> {
+        self.arena
+    }
+}
+
+impl<T> Drop for OpRecorder<'_, T> {
+    fn drop(&mut self) {
+        self.arena.stop_recording();
+    }
+}
@@ -0,0 +1,18 @@
+//! Effect of a node removal.
+
+use crate::NodeId;
+
+/// What [`NodeId::remove`] does with the children of the removed node.
+///
+/// Created by [`NodeId::removal_effect`].
+///
+/// [`NodeId::remove`]: struct.NodeId.html#method.remove
+/// [`NodeId::removal_effect`]: struct.NodeId.html#method.removal_effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovalEffect {
+    /// The children are spliced, in order, into the children of the given
+    /// parent, in place of the removed node.
+    SpliceToParent(NodeId),
+    /// The node is a root, so its children become roots of their own trees.
+    PromoteChildrenToRoots,
+}
@@ -97,6 +97,7 @@ impl DetachedSiblingsRange {
             let child_node = &mut arena[child];
             child_node.parent = new_parent;
             child_opt = child_node.next_sibling;
+            arena.update_cached_depths(child);
         }
 
         Ok(())
@@ -0,0 +1,32 @@
+//! Saved state of an arena.
+
+use crate::Arena;
+
+/// A saved state of an [`Arena`], to roll back to with [`Arena::restore`].
+///
+/// The snapshot holds a full copy of the nodes and of the free slots, so
+/// the IDs handed out before the snapshot are valid again after restoring
+/// it, and the IDs of the nodes created in between are not, even once their
+/// slots are allocated again.
+///
+/// Created by [`Arena::snapshot`].
+///
+/// [`Arena`]: struct.Arena.html
+/// [`Arena::restore`]: struct.Arena.html#method.restore
+/// [`Arena::snapshot`]: struct.Arena.html#method.snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot<T> {
+    arena: Arena<T>,
+}
+
+impl<T> Snapshot<T> {
+    /// Wraps the given copy of an arena.
+    pub(crate) fn new(arena: Arena<T>) -> Self {
+        Self { arena }
+    }
+
+    /// Returns the saved arena.
+    pub(crate) fn arena(&self) -> &Arena<T> {
+        &self.arena
+    }
+}
@@ -0,0 +1,71 @@
+//! Structural summary of a tree.
+
+/// Structural report of a (sub)tree.
+///
+/// Created by [`Arena::tree_summary`].
+///
+/// [`Arena::tree_summary`]: struct.Arena.html#method.tree_summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TreeSummary {
+    /// Number of nodes, including the root.
+    pub node_count: usize,
+    /// Number of nodes without children.
+    pub leaf_count: usize,
+    /// Number of edges on the longest path from the root to a leaf, 0 for a
+    /// single node.
+    pub height: usize,
+    /// Largest number of children of a single node.
+    pub max_branching: usize,
+    /// Whether the tree is height-balanced, as for
+    /// [`NodeId::is_balanced`].
+    ///
+    /// [`NodeId::is_balanced`]: struct.NodeId.html#method.is_balanced
+    pub is_balanced: bool,
+}
+
+/// Heights of the children subtrees of a node, accumulated during a
+/// post-order traversal to check the height balance of the node.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ChildHeights {
+    /// Number of children visited so far.
+    pub(crate) children: usize,
+    /// Smallest height of the children subtrees.
+    min: usize,
+    /// Largest height of the children subtrees.
+    max: usize,
+}
+
+impl ChildHeights {
+    /// Records the height of the subtree of the next child.
+    pub(crate) fn push(&mut self, height: usize) {
+        if self.children == 0 {
+            self.min = height;
+        }
+        self.children += 1;
+        self.min = self.min.min(height);
+        self.max = self.max.max(height);
+    }
+
+    /// Returns the height of the subtree of the node.
+    pub(crate) fn height(&self) -> usize {
+        if self.children == 0 {
+            0
+        } else {
+            self.max + 1
+        }
+    }
+
+    /// Returns whether the heights of the children subtrees differ by at
+    /// most 1.
+    ///
+    /// As in AVL trees, a missing child counts as an empty subtree of height
+    /// -1, so a node with a single child is only balanced if that child is a
+    /// leaf.
+    pub(crate) fn is_balanced(&self) -> bool {
+        match self.children {
+            0 => true,
+            1 => self.max == 0,
+            _ => self.max - self.min <= 1,
+        }
+    }
+}
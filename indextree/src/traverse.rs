@@ -2,6 +2,12 @@
 
 #![allow(clippy::redundant_closure_call)]
 
+#[cfg(not(feature = "std"))]
+use alloc::{collections::VecDeque, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
 use crate::{Arena, Node, NodeId};
 
 #[derive(Clone)]
@@ -106,6 +112,15 @@ macro_rules! new_iterator {
                     (None, Some(_)) | (None, None) => None,
                 }
             }
+
+            fn last(mut self) -> Option<NodeId> {
+                if self.0.tail.is_none() {
+                    // No tail to jump to, e.g. for the siblings of a root.
+                    return self.fold(None, |_, node| Some(node));
+                }
+                // The tail is the last node, no need to walk up to it.
+                ::core::iter::DoubleEndedIterator::next_back(&mut self)
+            }
         }
 
         #[allow(deprecated)]
@@ -177,6 +192,28 @@ new_iterator!(
     next_back = |tail| tail.next_sibling,
 );
 
+new_iterator!(
+    /// An iterator of the IDs of the siblings before a given node, in insertion order.
+    PrecedingSiblingsInOrder,
+    new = |arena, node| {
+        let last = arena[node].previous_sibling;
+        let first = last.map(|last| match arena[node].parent {
+            Some(parent) => arena[parent].first_child.unwrap_or(last),
+            None => {
+                let mut first = last;
+                while let Some(previous) = arena[first].previous_sibling {
+                    first = previous;
+                }
+                first
+            }
+        });
+
+        DoubleEndedIter::new(arena, first, last)
+    },
+    next = |head| head.next_sibling,
+    next_back = |tail| tail.previous_sibling,
+);
+
 new_iterator!(
     /// An iterator of the IDs of the siblings after a given node.
     FollowingSiblings,
@@ -213,6 +250,39 @@ new_iterator!(
     next = |node| node.previous_sibling,
 );
 
+#[derive(Clone)]
+/// An iterator of the IDs of a given node and its ancestors, together with
+/// their depth.
+///
+/// The depth of the topmost ancestor (the root) is 0.
+pub struct AncestorsWithDepth<'a, T> {
+    ancestors: Ancestors<'a, T>,
+    depth: usize,
+}
+
+impl<'a, T> AncestorsWithDepth<'a, T> {
+    pub(crate) fn new(arena: &'a Arena<T>, current: NodeId) -> Self {
+        let depth = Ancestors::new(arena, current).count() - 1;
+        Self {
+            ancestors: Ancestors::new(arena, current),
+            depth,
+        }
+    }
+}
+
+impl<T> Iterator for AncestorsWithDepth<'_, T> {
+    type Item = (NodeId, usize);
+
+    fn next(&mut self) -> Option<(NodeId, usize)> {
+        let node = self.ancestors.next()?;
+        let depth = self.depth;
+        self.depth = self.depth.saturating_sub(1);
+        Some((node, depth))
+    }
+}
+
+impl<T> core::iter::FusedIterator for AncestorsWithDepth<'_, T> {}
+
 #[derive(Clone)]
 /// An iterator of the IDs of a given node and its descendants, as a pre-order depth-first search where children are visited in insertion order.
 ///
@@ -236,8 +306,116 @@ impl<T> Iterator for Descendants<'_, T> {
     }
 }
 
+impl<T> DoubleEndedIterator for Descendants<'_, T> {
+    fn next_back(&mut self) -> Option<NodeId> {
+        while let Some(edge) = self.0.next_back() {
+            if let NodeEdge::Start(node) = edge {
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
 impl<T> core::iter::FusedIterator for Descendants<'_, T> {}
 
+#[derive(Clone)]
+/// An iterator of the IDs of a given node and its descendants, as a
+/// breadth-first search where children are visited in reverse insertion order.
+///
+/// i.e. node -> last child -> first child -> children of last child
+pub struct BreadthFirstReverse<'a, T> {
+    arena: &'a Arena<T>,
+    queue: VecDeque<NodeId>,
+}
+
+impl<'a, T> BreadthFirstReverse<'a, T> {
+    pub(crate) fn new(arena: &'a Arena<T>, current: NodeId) -> Self {
+        let mut queue = VecDeque::new();
+        queue.push_back(current);
+        Self { arena, queue }
+    }
+}
+
+impl<T> Iterator for BreadthFirstReverse<'_, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children(self.arena).rev());
+        Some(node)
+    }
+}
+
+impl<T> core::iter::FusedIterator for BreadthFirstReverse<'_, T> {}
+
+#[derive(Clone)]
+/// An iterator of the IDs of a given node and its descendants, as an in-order
+/// traversal of a binary tree.
+///
+/// The first child of a node is considered as its left child, and the next
+/// sibling of the first child as its right child. Any further children are
+/// ignored.
+///
+/// i.e. left subtree -> node -> right subtree
+pub struct InOrder<'a, T> {
+    arena: &'a Arena<T>,
+    stack: Vec<NodeId>,
+}
+
+impl<'a, T> InOrder<'a, T> {
+    pub(crate) fn new(arena: &'a Arena<T>, current: NodeId) -> Self {
+        let mut iter = Self {
+            arena,
+            stack: Vec::new(),
+        };
+        iter.push_left_branch(Some(current));
+        iter
+    }
+
+    /// Pushes the given node and its leftmost descendants.
+    fn push_left_branch(&mut self, mut node: Option<NodeId>) {
+        while let Some(id) = node {
+            self.stack.push(id);
+            node = self.arena[id].first_child;
+        }
+    }
+}
+
+impl<T> Iterator for InOrder<'_, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.stack.pop()?;
+        let right = self.arena[node]
+            .first_child
+            .and_then(|left| self.arena[left].next_sibling);
+        self.push_left_branch(right);
+        Some(node)
+    }
+}
+
+impl<T> core::iter::FusedIterator for InOrder<'_, T> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A navigation step of a depth-first pre-order walk, as yielded by
+/// [`NodeId::walk_events`].
+///
+/// [`NodeId::walk_events`]: struct.NodeId.html#method.walk_events
+pub enum WalkEvent {
+    /// Moved down to the given node, the first child of the previous node.
+    ///
+    /// The walk also starts by moving down to its root.
+    Down(NodeId),
+
+    /// Moved to the given node, the next sibling of the previous node.
+    Next(NodeId),
+
+    /// Moved back up to the given node, once all of its descendants were
+    /// visited.
+    Up(NodeId),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 /// Indicator if the node is at a start or endpoint of the tree
 pub enum NodeEdge {
@@ -469,6 +647,8 @@ pub struct Traverse<'a, T> {
     arena: &'a Arena<T>,
     root: NodeId,
     next: Option<NodeEdge>,
+    /// Next edge to yield from the back.
+    next_back: Option<NodeEdge>,
 }
 
 impl<'a, T> Traverse<'a, T> {
@@ -477,6 +657,7 @@ impl<'a, T> Traverse<'a, T> {
             arena,
             root: current,
             next: Some(NodeEdge::Start(current)),
+            next_back: Some(NodeEdge::End(current)),
         }
     }
 
@@ -488,12 +669,26 @@ impl<'a, T> Traverse<'a, T> {
         next.next_traverse(self.arena)
     }
 
+    /// Calculates the next node from the back.
+    fn next_of_next_back(&self, next_back: NodeEdge) -> Option<NodeEdge> {
+        if next_back == NodeEdge::Start(self.root) {
+            return None;
+        }
+        next_back.prev_traverse(self.arena)
+    }
+
     /// Returns a reference to the arena.
     #[inline]
     #[must_use]
     pub(crate) fn arena(&self) -> &Arena<T> {
         self.arena
     }
+
+    /// Skips the descendants of the given node, whose start was just yielded,
+    /// so that the next edge is the end of the node.
+    pub(crate) fn skip_subtree(&mut self, id: NodeId) {
+        self.next = Some(NodeEdge::End(id));
+    }
 }
 
 impl<T> Iterator for Traverse<'_, T> {
@@ -501,11 +696,29 @@ impl<T> Iterator for Traverse<'_, T> {
 
     fn next(&mut self) -> Option<NodeEdge> {
         let next = self.next.take()?;
-        self.next = self.next_of_next(next);
+        if self.next_back == Some(next) {
+            // Both ends met: this is the last edge.
+            self.next_back = None;
+        } else {
+            self.next = self.next_of_next(next);
+        }
         Some(next)
     }
 }
 
+impl<T> DoubleEndedIterator for Traverse<'_, T> {
+    fn next_back(&mut self) -> Option<NodeEdge> {
+        let next_back = self.next_back.take()?;
+        if self.next == Some(next_back) {
+            // Both ends met: this is the last edge.
+            self.next = None;
+        } else {
+            self.next_back = self.next_of_next_back(next_back);
+        }
+        Some(next_back)
+    }
+}
+
 impl<T> core::iter::FusedIterator for Traverse<'_, T> {}
 
 #[derive(Clone)]
@@ -548,3 +761,50 @@ impl<T> Iterator for ReverseTraverse<'_, T> {
 }
 
 impl<T> core::iter::FusedIterator for ReverseTraverse<'_, T> {}
+
+/// Returns an iterator pairing the nodes of two trees by their position in a
+/// breadth-first search, where children are visited in insertion order.
+///
+/// Both searches advance in lockstep. Once one tree is exhausted, the
+/// remaining nodes of the other tree are paired with `None`.
+///
+/// # Examples
+///
+/// ```
+/// # use indextree::Arena;
+/// let mut arena = Arena::new();
+/// let a = arena.new_node("a");
+/// let a_1 = a.append_value("a_1", &mut arena);
+/// let a_2 = a.append_value("a_2", &mut arena);
+/// let b = arena.new_node("b");
+/// let b_1 = b.append_value("b_1", &mut arena);
+///
+/// let mut iter = indextree::zip_breadth_first(&arena, a, b);
+/// assert_eq!(iter.next(), Some((Some(a), Some(b))));
+/// assert_eq!(iter.next(), Some((Some(a_1), Some(b_1))));
+/// assert_eq!(iter.next(), Some((Some(a_2), None)));
+/// assert_eq!(iter.next(), None);
+/// ```
+pub fn zip_breadth_first<T>(
+    arena: &Arena<T>,
+    root_a: NodeId,
+    root_b: NodeId,
+) -> impl Iterator<Item = (Option<NodeId>, Option<NodeId>)> + '_ {
+    /// Advances a breadth-first search using the given queue.
+    fn step<T>(arena: &Arena<T>, queue: &mut VecDeque<NodeId>) -> Option<NodeId> {
+        let node = queue.pop_front()?;
+        queue.extend(node.children(arena));
+        Some(node)
+    }
+
+    let mut queue_a = VecDeque::new();
+    queue_a.push_back(root_a);
+    let mut queue_b = VecDeque::new();
+    queue_b.push_back(root_b);
+    core::iter::from_fn(
+        move || match (step(arena, &mut queue_a), step(arena, &mut queue_b)) {
+            (None, None) => None,
+            pair => Some(pair),
+        },
+    )
+}
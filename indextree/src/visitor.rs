@@ -0,0 +1,22 @@
+//! Tree visitor.
+
+use crate::NodeId;
+
+/// A visitor receiving callbacks while walking a (sub)tree.
+///
+/// Every node is entered before any of its descendants and left after all of
+/// them, so calls to [`enter`] and [`leave`] are always balanced. The depth is
+/// relative to the node the walk started from, which has depth 0.
+///
+/// Used by [`NodeId::walk`].
+///
+/// [`enter`]: #tymethod.enter
+/// [`leave`]: #tymethod.leave
+/// [`NodeId::walk`]: struct.NodeId.html#method.walk
+pub trait TreeVisitor<T> {
+    /// Called when the node is entered, before any of its descendants.
+    fn enter(&mut self, id: NodeId, data: &T, depth: usize);
+
+    /// Called when the node is left, after all of its descendants.
+    fn leave(&mut self, id: NodeId, data: &T, depth: usize);
+}
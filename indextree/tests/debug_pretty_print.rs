@@ -218,3 +218,164 @@ fn non_debug_printable_type() {
     const EXPECTED: &str = "0\n`-- 1";
     assert_eq!(root.debug_pretty_print(&arena).to_string(), EXPECTED);
 }
+
+/// A type implementing neither `Display` nor `Debug`.
+struct Opaque {
+    id: u32,
+    label: &'static str,
+}
+
+#[test]
+fn pretty_print_with_closure() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(Opaque {
+        id: 0,
+        label: "root",
+    });
+    let n1 = root.append_value(
+        Opaque {
+            id: 1,
+            label: "one",
+        },
+        &mut arena,
+    );
+    n1.append_value(
+        Opaque {
+            id: 2,
+            label: "two",
+        },
+        &mut arena,
+    );
+    n1.append_value(
+        Opaque {
+            id: 3,
+            label: "three",
+        },
+        &mut arena,
+    );
+    root.append_value(
+        Opaque {
+            id: 4,
+            label: "four",
+        },
+        &mut arena,
+    );
+
+    let printable = root.pretty_print_with(&arena, |data, f| write!(f, "{}", data.label));
+    let expected = r#"root
+|-- one
+|   |-- two
+|   `-- three
+`-- four"#;
+    assert_eq!(printable.to_string(), expected);
+
+    let printable =
+        n1.pretty_print_with(&arena, |data, f| write!(f, "#{}\n{}", data.id, data.label));
+    let expected = r#"#1
+one
+|-- #2
+|   two
+`-- #3
+    three"#;
+    assert_eq!(printable.to_string(), expected);
+}
+
+#[test]
+fn max_children() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(label![]);
+    for i in 0..5 {
+        let child = root.append_value(label![i], &mut arena);
+        child.append_value(label![i, 0], &mut arena);
+    }
+
+    let printable = root.debug_pretty_print(&arena).max_children(2);
+    let expected = r#"root
+|-- 0
+|   `-- 0/0
+|-- 1
+|   `-- 1/0
+`-- ... (3 more)"#;
+    assert_eq!(printable.to_string(), expected);
+
+    let printable = root.debug_pretty_print(&arena).max_children(0);
+    assert_eq!(printable.to_string(), "root\n`-- ... (5 more)");
+
+    // The limit applies to each node, and is not reached by smaller nodes.
+    let (arena, root) = sample_tree();
+    let printable = root.debug_pretty_print(&arena).max_children(2);
+    let expected = r#"[]
+|-- [0]
+|-- [1]
+|   |-- [1, 0]
+|   |   `-- [1, 0, 0]
+|   |-- [1, 1]
+|   `-- ... (1 more)
+`-- ... (1 more)"#;
+    assert_eq!(format!("{:?}", printable), expected);
+    let printable = root.debug_pretty_print(&arena).max_children(3);
+    assert_eq!(
+        format!("{:?}", printable),
+        format!("{:?}", root.debug_pretty_print(&arena))
+    );
+}
+
+#[test]
+fn to_tree_string() {
+    let (arena, root) = sample_tree();
+    assert_eq!(
+        root.to_tree_string(&arena),
+        format!("{}", root.debug_pretty_print(&arena))
+    );
+    assert_eq!(
+        root.to_tree_string_debug(&arena),
+        format!("{:?}", root.debug_pretty_print(&arena))
+    );
+    let n1 = arena[root].first_child().unwrap();
+    assert_eq!(n1.to_tree_string(&arena), "0");
+}
+
+#[test]
+fn pretty_lines() {
+    let (arena, root) = sample_tree();
+    let lines: Vec<_> = root.pretty_lines(&arena).collect();
+    assert_eq!(lines.len(), root.descendants(&arena).count());
+    assert_eq!(lines[0], "root");
+    assert_eq!(lines[4], "|   |   `-- 1/0/0");
+    assert_eq!(lines.join("\n"), root.to_tree_string(&arena));
+
+    let n2 = arena[root].last_child().unwrap();
+    let lines: Vec<_> = n2.pretty_lines(&arena).collect();
+    assert_eq!(
+        lines,
+        ["2", "|-- 2/0", "|   `-- 2/0/0", "`-- 2/1", "    `-- 2/1/0"]
+    );
+
+    // Data spanning several lines is kept in a single item.
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let a = root.append_value("a\nmultiline", &mut arena);
+    a.append_value("a_1\n", &mut arena);
+    root.append_value("b\n\nmultiline", &mut arena);
+    let lines: Vec<_> = root.pretty_lines(&arena).collect();
+    assert_eq!(
+        lines,
+        [
+            "root",
+            "|-- a\n|   multiline",
+            "|   `-- a_1",
+            "`-- b\n    \n    multiline"
+        ]
+    );
+    assert_eq!(lines.join("\n"), root.to_tree_string(&arena));
+
+    // Empty data still shows its branch, and trailing line breaks are dropped.
+    let mut arena = Arena::new();
+    let root = arena.new_node("");
+    let a = root.append_value("a\nb", &mut arena);
+    a.append_value("", &mut arena);
+    a.append_value("c\n", &mut arena);
+    root.append_value("", &mut arena);
+    let lines: Vec<_> = root.pretty_lines(&arena).collect();
+    assert_eq!(lines, ["", "|-- a\n|   b", "|   |-- ", "|   `-- c", "`-- "]);
+}
@@ -1,6 +1,6 @@
 //! Insertion errors.
 
-use indextree::Arena;
+use indextree::{Arena, NodeError};
 
 #[test]
 fn append_self() {
@@ -29,3 +29,216 @@ fn insert_before_self() {
     let n1 = arena.new_node("1");
     assert!(n1.checked_insert_before(n1, &mut arena).is_err());
 }
+
+#[test]
+fn insert_between() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+
+    assert!(matches!(
+        n1_1.insert_between(n1_1, n1_2, &mut arena),
+        Err(NodeError::InsertAfterSelf)
+    ));
+    assert!(matches!(
+        n1_2.insert_between(n1_1, n1_2, &mut arena),
+        Err(NodeError::InsertBeforeSelf)
+    ));
+    assert!(matches!(
+        n2.insert_between(n1_1, n1_3, &mut arena),
+        Err(NodeError::NotAdjacentSiblings)
+    ));
+    assert!(matches!(
+        n2.insert_between(n1_2, n1_1, &mut arena),
+        Err(NodeError::NotAdjacentSiblings)
+    ));
+    assert!(matches!(
+        n1.insert_between(n1_1, n1_2, &mut arena),
+        Err(NodeError::AppendAncestor)
+    ));
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_1, n1_2, n1_3]);
+
+    // Move a node from another tree between the first and second children.
+    assert!(n2_1.insert_between(n1_1, n1_2, &mut arena).is_ok());
+    assert_eq!(
+        n1.children(&arena).collect::<Vec<_>>(),
+        &[n1_1, n2_1, n1_2, n1_3]
+    );
+    assert_eq!(arena[n2_1].parent(), Some(n1));
+    assert!(arena[n2].first_child().is_none());
+
+    n2.remove(&mut arena);
+    assert!(matches!(
+        n2.insert_between(n1_2, n1_3, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == n2
+    ));
+}
+
+#[test]
+fn swap_siblings() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n1_3_1 = n1_3.append_value("1_3_1", &mut arena);
+
+    assert!(n1_1.swap_siblings(n1_3, &mut arena).is_ok());
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_3, n1_2, n1_1]);
+    assert_eq!(arena[n1].first_child(), Some(n1_3));
+    assert_eq!(arena[n1].last_child(), Some(n1_1));
+    assert_eq!(
+        n1.children(&arena).rev().collect::<Vec<_>>(),
+        &[n1_1, n1_2, n1_3]
+    );
+    assert_eq!(arena[n1_3_1].parent(), Some(n1_3));
+
+    // Adjacent siblings, in both argument orders.
+    assert!(n1_3.swap_siblings(n1_2, &mut arena).is_ok());
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2, n1_3, n1_1]);
+    assert!(n1_1.swap_siblings(n1_3, &mut arena).is_ok());
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2, n1_1, n1_3]);
+    assert!(n1_1.swap_siblings(n1_1, &mut arena).is_ok());
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2, n1_1, n1_3]);
+
+    assert!(matches!(
+        n1_1.swap_siblings(n1_3_1, &mut arena),
+        Err(NodeError::NotSiblings)
+    ));
+    n1_2.remove(&mut arena);
+    assert!(matches!(
+        n1_1.swap_siblings(n1_2, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == n1_2
+    ));
+
+    // Roots are only siblings if they are part of the same sibling chain.
+    let n2 = arena.new_node("2");
+    assert!(matches!(
+        n1.swap_siblings(n2, &mut arena),
+        Err(NodeError::NotSiblings)
+    ));
+    let n3 = arena.new_node("3");
+    let n4 = arena.new_node("4");
+    let n5 = arena.new_node("5");
+    n3.insert_after(n4, &mut arena);
+    n4.insert_after(n5, &mut arena);
+    let n6 = arena.new_node("6");
+    let n7 = arena.new_node("7");
+    n6.insert_after(n7, &mut arena);
+    assert!(matches!(
+        n3.swap_siblings(n7, &mut arena),
+        Err(NodeError::NotSiblings)
+    ));
+    assert!(matches!(
+        n2.swap_siblings(n4, &mut arena),
+        Err(NodeError::NotSiblings)
+    ));
+    assert_eq!(
+        n3.following_siblings(&arena).collect::<Vec<_>>(),
+        &[n3, n4, n5]
+    );
+    assert_eq!(n6.following_siblings(&arena).collect::<Vec<_>>(), &[n6, n7]);
+    assert!(n5.swap_siblings(n3, &mut arena).is_ok());
+    assert_eq!(
+        n5.following_siblings(&arena).collect::<Vec<_>>(),
+        &[n5, n4, n3]
+    );
+    assert_eq!(arena[n5].previous_sibling(), None);
+}
+
+#[test]
+fn move_before_after() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+
+    // Move a node with its subtree from one parent to another.
+    assert!(n1_2_1.move_before(n1_1_2, &mut arena).is_ok());
+    assert_eq!(arena[n1_2_1].parent(), Some(n1_1));
+    assert_eq!(
+        n1_1.children(&arena).collect::<Vec<_>>(),
+        &[n1_1_1, n1_2_1, n1_1_2]
+    );
+    assert_eq!(n1_2.children(&arena).collect::<Vec<_>>(), &[n1_2_2]);
+    assert_eq!(arena[n1_2_1_1].parent(), Some(n1_2_1));
+
+    assert!(n1_1_1.move_after(n1_2_2, &mut arena).is_ok());
+    assert_eq!(n1_2.children(&arena).collect::<Vec<_>>(), &[n1_2_2, n1_1_1]);
+    assert_eq!(n1_1.children(&arena).collect::<Vec<_>>(), &[n1_2_1, n1_1_2]);
+
+    assert!(matches!(
+        n1_1.move_before(n1_1, &mut arena),
+        Err(NodeError::InsertBeforeSelf)
+    ));
+    assert!(matches!(
+        n1_1.move_after(n1_1, &mut arena),
+        Err(NodeError::InsertAfterSelf)
+    ));
+    assert!(matches!(
+        n1_1.move_before(n1_2_1_1, &mut arena),
+        Err(NodeError::AppendAncestor)
+    ));
+    assert!(matches!(
+        n1.move_after(n1_2_2, &mut arena),
+        Err(NodeError::AppendAncestor)
+    ));
+    n1_1_2.remove(&mut arena);
+    assert!(matches!(
+        n1_2_2.move_after(n1_1_2, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == n1_1_2
+    ));
+    assert_eq!(arena[n1_2_2].parent(), Some(n1_2));
+}
+
+#[test]
+fn validate_ids() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    assert!(arena.validate_ids(&[]).is_ok());
+    assert!(arena.validate_ids(&[n1, n1_1, n1_2]).is_ok());
+
+    n1_1.remove(&mut arena);
+    assert!(matches!(
+        arena.validate_ids(&[n1, n1_1, n1_2]),
+        Err(NodeError::RemovedNode(id)) if id == n1_1
+    ));
+
+    // The slot is reused, but the old id is still stale.
+    let n2 = arena.new_node("2");
+    assert!(matches!(
+        arena.validate_ids(&[n2, n1_1]),
+        Err(NodeError::RemovedNode(id)) if id == n1_1
+    ));
+
+    let mut other = Arena::new();
+    let foreign = other.new_node("foreign");
+    other.new_node("foreign");
+    other.new_node("foreign");
+    let out_of_range = other.new_node("foreign");
+    assert!(matches!(
+        arena.validate_ids(&[n1, out_of_range]),
+        Err(NodeError::NotFound(id)) if id == out_of_range
+    ));
+    // Foreign ids in range cannot be told apart from local ones.
+    assert!(arena.validate_ids(&[foreign]).is_ok());
+
+    arena.clear();
+    let n3 = arena.new_node("3");
+    assert!(arena.validate_ids(&[n3]).is_ok());
+    assert!(matches!(
+        arena.validate_ids(&[n3, n1]),
+        Err(NodeError::NotFound(id)) if id == n1
+    ));
+}
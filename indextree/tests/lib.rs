@@ -1,4 +1,4 @@
-use indextree::{Arena, NodeError};
+use indextree::{Arena, FrozenArena, NodeError, NodeId, ParseError, TreeSummary, TreeVisitor};
 #[cfg(feature = "par_iter")]
 use rayon::prelude::*;
 
@@ -295,3 +295,2938 @@ fn reverse_children() {
     assert_eq!(iter.next(), Some(1));
     assert_eq!(iter.next(), None);
 }
+
+#[test]
+fn compute_intervals() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    n1_2.append_value("1_2_1", &mut arena);
+    n1_2.append_value("1_2_2", &mut arena);
+    let n2 = arena.new_node("2");
+    n2.append_value("2_1", &mut arena);
+    let removed = n1.append_value("removed", &mut arena);
+    removed.remove(&mut arena);
+
+    let index = arena.compute_intervals();
+    let ids = arena
+        .iter()
+        .filter(|node| !node.is_removed())
+        .map(|node| arena.get_node_id(node).unwrap())
+        .collect::<Vec<_>>();
+    for &ancestor in &ids {
+        for &node in &ids {
+            assert_eq!(
+                index.is_descendant(ancestor, node),
+                node.ancestors(&arena).any(|id| id == ancestor),
+                "ancestor: {}, node: {}",
+                ancestor,
+                node
+            );
+        }
+    }
+    assert!(!index.is_descendant(n1, removed));
+}
+
+#[test]
+fn extend_chain() {
+    let mut arena = Arena::with_capacity(1);
+    let root = arena.new_node(0);
+    root.append_value(-1, &mut arena);
+    let deepest = arena.extend_chain(root, 1..=100);
+    assert_eq!(deepest.ancestors(&arena).count(), 101);
+    assert_eq!(arena[deepest].first_child(), None);
+    assert_eq!(
+        root.descendants(&arena)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>(),
+        [0, -1].into_iter().chain(1..=100).collect::<Vec<_>>()
+    );
+    assert!(root
+        .descendants(&arena)
+        .skip(2)
+        .all(|id| id.children(&arena).count() <= 1));
+
+    assert_eq!(arena.extend_chain(deepest, None), deepest);
+}
+
+#[test]
+fn insert_already_in_place() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let n1 = root.append_value("1", &mut arena);
+    let n2 = root.append_value("2", &mut arena);
+    let n3 = root.append_value("3", &mut arena);
+    // root
+    // |-- 1
+    // |-- 2
+    // `-- 3
+
+    // Checks that the parent and sibling links of all the nodes are intact.
+    let assert_links = |arena: &Arena<&str>| {
+        assert_eq!(arena[root].parent(), None);
+        assert_eq!(arena[root].first_child(), Some(n1));
+        assert_eq!(arena[root].last_child(), Some(n3));
+        let links = [
+            (None, n1, Some(n2)),
+            (Some(n1), n2, Some(n3)),
+            (Some(n2), n3, None),
+        ];
+        for (previous, id, next) in links {
+            let node = &arena[id];
+            assert_eq!(node.parent(), Some(root));
+            assert_eq!(node.previous_sibling(), previous);
+            assert_eq!(node.next_sibling(), next);
+            assert_eq!(node.first_child(), None);
+            assert_eq!(node.last_child(), None);
+        }
+        assert_eq!(root.children(arena).collect::<Vec<_>>(), &[n1, n2, n3]);
+        assert_eq!(
+            root.children(arena).rev().collect::<Vec<_>>(),
+            &[n3, n2, n1]
+        );
+    };
+
+    assert!(n1.checked_insert_after(n2, &mut arena).is_ok());
+    assert_links(&arena);
+    assert!(n3.checked_insert_before(n2, &mut arena).is_ok());
+    assert_links(&arena);
+    assert!(root.checked_append(n3, &mut arena).is_ok());
+    assert_links(&arena);
+    assert!(root.checked_prepend(n1, &mut arena).is_ok());
+    assert_links(&arena);
+}
+
+#[test]
+fn get_disjoint_mut() {
+    let mut arena = Arena::new();
+    let a = arena.new_node(1);
+    let b = arena.new_node(2);
+    let c = arena.new_node(3);
+
+    let [c_node, a_node, b_node] = arena.get_disjoint_mut([c, a, b]).unwrap();
+    *a_node.get_mut() += 10;
+    *b_node.get_mut() += 20;
+    *c_node.get_mut() += 30;
+    assert_eq!(
+        arena.iter().map(|node| *node.get()).collect::<Vec<_>>(),
+        [11, 22, 33]
+    );
+
+    assert!(arena.get_disjoint_mut([a, b, b]).is_none());
+    assert!(arena.get_disjoint_mut([c, a, c]).is_none());
+
+    let mut other = Arena::new();
+    let foreign = (0..5).map(|i| other.new_node(i)).last().unwrap();
+    assert!(arena.get_disjoint_mut([a, foreign, b]).is_none());
+    assert!(arena.get_disjoint_mut::<0>([]).is_some());
+}
+
+#[test]
+fn ancestors_with_depth() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    let deepest = arena.extend_chain(root, 1..5);
+    let path = deepest.ancestors_with_depth(&arena).collect::<Vec<_>>();
+    assert_eq!(path.len(), 5);
+    assert_eq!(path.last(), Some(&(root, 0)));
+    assert!(path.iter().all(|&(id, depth)| *arena[id].get() == depth));
+    assert_eq!(
+        root.ancestors_with_depth(&arena).collect::<Vec<_>>(),
+        &[(root, 0)]
+    );
+}
+
+#[test]
+fn non_trivial_nodes() {
+    let mut arena = Arena::new();
+    let orphan1 = arena.new_node("orphan1");
+    let a = arena.new_node("a");
+    let a_1 = a.append_value("a_1", &mut arena);
+    let orphan2 = arena.new_node("orphan2");
+    let b = arena.new_node("b");
+    let b_1 = b.append_value("b_1", &mut arena);
+    let b_1_1 = b_1.append_value("b_1_1", &mut arena);
+    let detached = a.append_value("detached", &mut arena);
+    detached.detach(&mut arena);
+    let removed = b.append_value("removed", &mut arena);
+    removed.remove(&mut arena);
+
+    let nodes = arena.non_trivial_nodes().collect::<Vec<_>>();
+    assert_eq!(nodes, &[a, a_1, b, b_1, b_1_1]);
+    assert!(!nodes.contains(&orphan1));
+    assert!(!nodes.contains(&orphan2));
+}
+
+#[test]
+fn trim_tail() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(1);
+    let n2 = arena.new_node(2);
+    let n3 = n1.append_value(3, &mut arena);
+    assert_eq!(arena.trim_tail(), 0);
+
+    n3.remove(&mut arena);
+    assert_eq!(arena.trim_tail(), 1);
+    assert_eq!(arena.count(), 2);
+    assert!(!n1.is_removed(&arena));
+    assert!(!n2.is_removed(&arena));
+    assert_eq!(n1.children(&arena).count(), 0);
+    assert_eq!(*arena[n1].get(), 1);
+    assert_eq!(*arena[n2].get(), 2);
+    // The generation is kept, so the live ids are still current.
+    assert!(n1.is_current(&arena));
+    assert!(n2.is_current(&arena));
+
+    n1.remove(&mut arena);
+    assert_eq!(arena.trim_tail(), 0);
+    n2.remove(&mut arena);
+    assert_eq!(arena.trim_tail(), 2);
+    assert!(arena.is_empty());
+    let n4 = arena.new_node(4);
+    assert_eq!(usize::from(n4), 1);
+}
+
+#[test]
+fn trim_tail_stale_ids() {
+    let mut arena = Arena::new();
+    let a = arena.new_node("a");
+    let b = arena.new_node("b");
+    let c = arena.new_node("c");
+    c.remove(&mut arena);
+    b.remove(&mut arena);
+    assert_eq!(arena.trim_tail(), 2);
+
+    // The slots are pushed again, and reused afterwards.
+    let d = arena.new_node("d");
+    let e = arena.new_node("e");
+    assert_eq!(usize::from(d), usize::from(b));
+    assert_eq!(usize::from(e), usize::from(c));
+    assert!(b.is_removed(&arena));
+    assert!(c.is_removed(&arena));
+    assert_ne!(b, d);
+    assert_ne!(c, e);
+    assert_eq!(*arena[d].get(), "d");
+
+    e.remove(&mut arena);
+    let f = arena.new_node("f");
+    assert_eq!(usize::from(f), usize::from(c));
+    assert!(c.is_removed(&arena));
+    assert!(e.is_removed(&arena));
+    assert_ne!(c, f);
+    assert!(!a.is_removed(&arena));
+}
+
+#[test]
+fn clone_from() {
+    let mut source = Arena::new();
+    let root = source.new_node(0);
+    let deepest = source.extend_chain(root, 1..10);
+    let removed = root.append_value(-1, &mut source);
+    removed.remove(&mut source);
+
+    let mut target = Arena::with_capacity(64);
+    target.new_node(42);
+    let capacity = target.capacity();
+    target.clone_from(&source);
+    assert_eq!(target, source);
+    assert_eq!(target.capacity(), capacity);
+    assert_eq!(deepest.ancestors(&target).count(), 10);
+
+    // The free list is cloned too.
+    let reused = target.new_node(100);
+    assert_eq!(reused, source.new_node(100));
+}
+
+#[test]
+fn is_current() {
+    let mut arena = Arena::new();
+    let before = arena.new_node(1);
+    let before_child = before.append_value(2, &mut arena);
+    assert!(before.is_current(&arena));
+    assert!(before_child.is_current(&arena));
+
+    arena.clear();
+    let after = arena.new_node(3);
+    assert!(!before.is_current(&arena));
+    assert!(!before_child.is_current(&arena));
+    assert!(after.is_current(&arena));
+    // The generation is not part of the equality of ids.
+    assert_eq!(before, after);
+    assert_eq!(arena.get_node_id(&arena[after]), Some(after));
+
+    arena.clear();
+    assert!(!after.is_current(&arena));
+}
+
+#[test]
+fn sift_up_by_swapping_data() {
+    // A min-heap as an explicit binary tree.
+    //
+    // 1
+    // |-- 3
+    // |   |-- 4
+    // |   `-- 5
+    // `-- 2
+    let mut arena = Arena::new();
+    let root = arena.new_node(1);
+    let n3 = root.append_value(3, &mut arena);
+    root.append_value(2, &mut arena);
+    n3.append_value(4, &mut arena);
+    let n5 = n3.append_value(5, &mut arena);
+
+    // Decrease the key of the last leaf and sift it up.
+    *arena[n5].get_mut() = 0;
+    let mut current = n5;
+    while let Some(parent) = arena[current].parent() {
+        if arena[parent].get() <= arena[current].get() {
+            break;
+        }
+        assert!(current.swap_data_with_parent(&mut arena));
+        current = parent;
+    }
+
+    assert_eq!(current, root);
+    assert_eq!(
+        root.descendants(&arena)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>(),
+        [0, 1, 4, 3, 2]
+    );
+    assert_eq!(arena[n5].parent(), Some(n3));
+    assert!(!root.swap_data_with_parent(&mut arena));
+    assert!(!n3.swap_data_with_child(root, &mut arena));
+}
+
+#[test]
+fn deepest_descendant() {
+    // 0
+    // |-- 1
+    // |   `-- 2
+    // |-- 3
+    // |   |-- 4
+    // |   |   `-- 5
+    // |   |       `-- 6
+    // |   `-- 7
+    // |       `-- 8
+    // |           `-- 9
+    // `-- 10
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    arena.extend_chain(root, 1..=2);
+    let n3 = root.append_value(3, &mut arena);
+    let n6 = arena.extend_chain(n3, 4..=6);
+    let n9 = arena.extend_chain(n3, 7..=9);
+    root.append_value(10, &mut arena);
+
+    assert_eq!(root.deepest_descendant(&arena), n6);
+    assert_eq!(n3.deepest_descendant(&arena), n6);
+    assert_eq!(arena[n9].parent().unwrap().deepest_descendant(&arena), n9);
+    assert_eq!(n6.deepest_descendant(&arena), n6);
+}
+
+#[test]
+fn iter_tree_order() {
+    let mut arena = Arena::new();
+    let a = arena.new_node("a");
+    let b = arena.new_node("b");
+    let a_1 = a.append_value("a_1", &mut arena);
+    let b_1 = b.append_value("b_1", &mut arena);
+    let a_1_1 = a_1.append_value("a_1_1", &mut arena);
+    let a_2 = a.append_value("a_2", &mut arena);
+    let c = arena.new_node("c");
+    let removed = b.append_value("removed", &mut arena);
+    removed.remove(&mut arena);
+    let b_2 = b.append_value("b_2", &mut arena);
+
+    let order = arena.iter_tree_order().collect::<Vec<_>>();
+    assert_eq!(
+        order.len(),
+        arena.iter().filter(|node| !node.is_removed()).count()
+    );
+    assert_eq!(order, &[a, a_1, a_1_1, a_2, b, b_1, b_2, c]);
+    for root in arena.roots() {
+        let start = order.iter().position(|&id| id == root).unwrap();
+        let tree = root.descendants(&arena).collect::<Vec<_>>();
+        assert_eq!(&order[start..start + tree.len()], tree.as_slice());
+    }
+}
+
+#[test]
+fn build_with_reserve() {
+    let (arena, root) = Arena::build_with(0, |builder| {
+        builder.reserve(1000);
+        let capacity = builder.arena().capacity();
+        for i in 0..111 {
+            let value = i * 9 + 1;
+            builder.append_with(value, |builder| {
+                for j in 1..=8 {
+                    builder.append(value + j);
+                }
+            });
+        }
+        assert_eq!(builder.arena().count(), 1000);
+        assert_eq!(builder.arena().capacity(), capacity);
+    });
+
+    assert_eq!(root.children(&arena).count(), 111);
+    assert_eq!(
+        root.descendants(&arena)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>(),
+        (0..1000).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn first_and_last_leaf() {
+    // root
+    // |-- 0
+    // |   |-- 0_0
+    // |   |   `-- 0_0_0
+    // |   `-- 0_1
+    // `-- 1
+    //     |-- 1_0
+    //     `-- 1_1
+    //         `-- 1_1_0
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let n0 = root.append_value("0", &mut arena);
+    let n0_0 = n0.append_value("0_0", &mut arena);
+    let n0_0_0 = n0_0.append_value("0_0_0", &mut arena);
+    let n0_1 = n0.append_value("0_1", &mut arena);
+    let n1 = root.append_value("1", &mut arena);
+    let n1_0 = n1.append_value("1_0", &mut arena);
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_0 = n1_1.append_value("1_1_0", &mut arena);
+
+    assert_eq!(root.first_leaf(&arena), n0_0_0);
+    assert_eq!(root.last_leaf(&arena), n1_1_0);
+    assert_eq!(n0.first_leaf(&arena), n0_0_0);
+    assert_eq!(n0.last_leaf(&arena), n0_1);
+    assert_eq!(n1.first_leaf(&arena), n1_0);
+    assert_eq!(n1.last_leaf(&arena), n1_1_0);
+    assert_eq!(n0_1.first_leaf(&arena), n0_1);
+    assert_eq!(n0_1.last_leaf(&arena), n0_1);
+}
+
+#[test]
+fn breadth_first_reverse() {
+    // 0
+    // |-- 1
+    // |   |-- 4
+    // |   `-- 5
+    // |-- 2
+    // `-- 3
+    //     |-- 6
+    //     |   `-- 8
+    //     `-- 7
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    let n1 = root.append_value(1, &mut arena);
+    root.append_value(2, &mut arena);
+    let n3 = root.append_value(3, &mut arena);
+    n1.append_value(4, &mut arena);
+    n1.append_value(5, &mut arena);
+    let n6 = n3.append_value(6, &mut arena);
+    n3.append_value(7, &mut arena);
+    n6.append_value(8, &mut arena);
+
+    assert_eq!(
+        root.breadth_first_reverse(&arena)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>(),
+        [0, 3, 2, 1, 7, 6, 5, 4, 8]
+    );
+}
+
+#[test]
+fn in_order() {
+    fn insert(arena: &mut Arena<Option<i32>>, root: NodeId, value: i32) {
+        // Each node always has two children (left and right), possibly
+        // empty (`None`).
+        let mut current = root;
+        loop {
+            let data = *arena[current].get();
+            match data {
+                None => {
+                    *arena[current].get_mut() = Some(value);
+                    current.append_value(None, arena);
+                    current.append_value(None, arena);
+                    return;
+                }
+                Some(data) => {
+                    let left = arena[current].first_child().unwrap();
+                    current = if value < data {
+                        left
+                    } else {
+                        arena[left].next_sibling().unwrap()
+                    };
+                }
+            }
+        }
+    }
+
+    let mut arena = Arena::new();
+    let root = arena.new_node(None);
+    let values = [50, 30, 70, 20, 40, 60, 80, 35, 45, 65, 10];
+    for value in values {
+        insert(&mut arena, root, value);
+    }
+
+    let sorted = root
+        .in_order(&arena)
+        .filter_map(|id| *arena[id].get())
+        .collect::<Vec<_>>();
+    let mut expected = values.to_vec();
+    expected.sort_unstable();
+    assert_eq!(sorted, expected);
+
+    // Only the first two children are considered.
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    let left = root.append_value(1, &mut arena);
+    let right = root.append_value(2, &mut arena);
+    let extra = root.append_value(3, &mut arena);
+    extra.append_value(4, &mut arena);
+    assert_eq!(
+        root.in_order(&arena).collect::<Vec<_>>(),
+        &[left, root, right]
+    );
+}
+
+#[test]
+fn reorder_children() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let a = root.append_value("a", &mut arena);
+    let b = root.append_value("b", &mut arena);
+    let c = root.append_value("c", &mut arena);
+    let d = root.append_value("d", &mut arena);
+    let b_1 = b.append_value("b_1", &mut arena);
+    let other = arena.new_node("other");
+
+    assert!(root.reorder_children(&[d, b, a, c], &mut arena).is_ok());
+    assert_eq!(root.children(&arena).collect::<Vec<_>>(), &[d, b, a, c]);
+    assert_eq!(
+        root.children(&arena).rev().collect::<Vec<_>>(),
+        &[c, a, b, d]
+    );
+    assert_eq!(arena[root].first_child(), Some(d));
+    assert_eq!(arena[root].last_child(), Some(c));
+    assert_eq!(b.children(&arena).collect::<Vec<_>>(), &[b_1]);
+
+    let bogus: [&[_]; 5] = [
+        &[d, b, a],
+        &[d, b, a, c, a],
+        &[d, b, a, a],
+        &[d, b, a, other],
+        &[d, b, a, b_1],
+    ];
+    for order in bogus {
+        assert!(matches!(
+            root.reorder_children(order, &mut arena),
+            Err(NodeError::InvalidPermutation)
+        ));
+        assert_eq!(root.children(&arena).collect::<Vec<_>>(), &[d, b, a, c]);
+    }
+
+    assert!(other.reorder_children(&[], &mut arena).is_ok());
+    assert!(b.reorder_children(&[b_1], &mut arena).is_ok());
+}
+
+#[test]
+fn append_default() {
+    let mut arena: Arena<(u32, Option<&str>)> = Arena::new();
+    let root = arena.new_node((1, Some("root")));
+    let first = root.append_default(&mut arena);
+    let second = root.append_default(&mut arena);
+    assert_eq!(*arena[first].get(), (0, None));
+    assert_eq!(*arena[second].get(), (0, None));
+    assert_eq!(root.children(&arena).collect::<Vec<_>>(), &[first, second]);
+}
+
+#[test]
+fn make_root() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    let deepest = arena.extend_chain(root, 1..10);
+    let nested = arena[deepest].parent().unwrap();
+    let sibling = nested.append_value(100, &mut arena);
+
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[root]);
+    assert_eq!(nested.make_root(&mut arena), nested);
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[root, nested]);
+    assert_eq!(arena[nested].parent(), None);
+    assert_eq!(arena[nested].previous_sibling(), None);
+    assert_eq!(arena[nested].next_sibling(), None);
+    assert_eq!(
+        nested.children(&arena).collect::<Vec<_>>(),
+        &[deepest, sibling]
+    );
+    assert_eq!(root.descendants(&arena).count(), 8);
+}
+
+#[test]
+fn preceding_siblings_in_order() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let first = root.append_value("first", &mut arena);
+    let second = root.append_value("second", &mut arena);
+    let third = root.append_value("third", &mut arena);
+    root.append_value("fourth", &mut arena);
+
+    assert_eq!(
+        third
+            .preceding_siblings_in_order(&arena)
+            .collect::<Vec<_>>(),
+        &[first, second]
+    );
+    assert_eq!(
+        third
+            .preceding_siblings_in_order(&arena)
+            .rev()
+            .collect::<Vec<_>>(),
+        &[second, first]
+    );
+    assert_eq!(first.preceding_siblings_in_order(&arena).count(), 0);
+    assert_eq!(root.preceding_siblings_in_order(&arena).count(), 0);
+
+    // Siblings without parent.
+    let a = arena.new_node("a");
+    let b = arena.new_node("b");
+    let c = arena.new_node("c");
+    a.insert_after(b, &mut arena);
+    b.insert_after(c, &mut arena);
+    assert_eq!(
+        c.preceding_siblings_in_order(&arena).collect::<Vec<_>>(),
+        &[a, b]
+    );
+}
+
+#[test]
+fn removed_node_error_context() {
+    let mut arena = Arena::new();
+    let parent = arena.new_node("parent");
+    let child = arena.new_node("child");
+    child.remove(&mut arena);
+
+    let err = parent.checked_append(child, &mut arena).unwrap_err();
+    assert!(matches!(err, NodeError::RemovedNode(id) if id == child));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Removed node {} cannot have any parent, siblings, and children",
+            child
+        )
+    );
+    assert!(matches!(
+        child.checked_append(parent, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == child
+    ));
+    assert!(matches!(
+        parent.checked_prepend(child, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == child
+    ));
+    assert!(matches!(
+        parent.checked_insert_after(child, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == child
+    ));
+    assert!(matches!(
+        parent.checked_insert_before(child, &mut arena),
+        Err(NodeError::RemovedNode(id)) if id == child
+    ));
+}
+
+#[test]
+fn count_descendants_matching() {
+    let (arena, root) = Arena::build_with(0, |builder| {
+        for i in 1..=3 {
+            builder.append_with(i * 10, |builder| {
+                for j in 1..=i {
+                    builder.append(i * 10 + j);
+                }
+            });
+        }
+    });
+    // 0
+    // |-- 10
+    // |   `-- 11
+    // |-- 20
+    // |   |-- 21
+    // |   `-- 22
+    // `-- 30
+    //     |-- 31
+    //     |-- 32
+    //     `-- 33
+    assert_eq!(root.count_descendants_matching(&arena, |v| v % 2 == 0), 6);
+    assert_eq!(root.count_descendants_matching(&arena, |_| true), 10);
+    let n20 = root.children(&arena).nth(1).unwrap();
+    assert_eq!(n20.count_descendants_matching(&arena, |v| v % 2 == 0), 2);
+    assert_eq!(n20.count_descendants_matching(&arena, |v| *v > 100), 0);
+}
+
+#[test]
+fn unfold() {
+    let (arena, root) = Arena::unfold(String::new(), 2, |path: &String| {
+        (
+            path.clone(),
+            vec![format!("{}0", path), format!("{}1", path)],
+        )
+    });
+    assert_eq!(arena.count(), 7);
+    assert_eq!(
+        root.descendants(&arena)
+            .map(|id| arena[id].get().as_str())
+            .collect::<Vec<_>>(),
+        ["", "0", "00", "01", "1", "10", "11"]
+    );
+    // Built breadth-first.
+    assert_eq!(
+        arena
+            .iter()
+            .map(|node| node.get().len())
+            .collect::<Vec<_>>(),
+        [0, 1, 1, 2, 2, 2, 2]
+    );
+    assert!(root
+        .descendants(&arena)
+        .filter(|&id| arena[id].get().len() < 2)
+        .all(|id| id.children(&arena).count() == 2));
+
+    let (arena, root) = Arena::unfold(0, 0, |&depth| (depth, vec![depth + 1]));
+    assert_eq!(arena.count(), 1);
+    assert_eq!(*arena[root].get(), 0);
+}
+
+#[test]
+fn try_from_parent_ids() {
+    let items = vec![
+        (None, 0),
+        (Some(0), 1),
+        (None, 2),
+        (Some(1), 3),
+        (Some(0), 4),
+        (Some(2), 5),
+        (Some(3), 6),
+    ];
+    let (arena, ids) = Arena::try_from_parent_ids(items.clone()).unwrap();
+    assert_eq!(ids.len(), items.len());
+    for (id, (parent, data)) in ids.iter().zip(&items) {
+        assert_eq!(arena[*id].get(), data);
+        assert_eq!(arena[*id].parent(), parent.map(|parent| ids[parent]));
+    }
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[ids[0], ids[2]]);
+    assert_eq!(
+        ids[0].descendants(&arena).collect::<Vec<_>>(),
+        &[ids[0], ids[1], ids[3], ids[6], ids[4]]
+    );
+
+    assert!(matches!(
+        Arena::try_from_parent_ids(vec![(None, 0), (Some(1), 1)]),
+        Err(NodeError::InvalidParentIndex(1))
+    ));
+    assert!(matches!(
+        Arena::try_from_parent_ids(vec![(None, 0), (Some(0), 1), (Some(5), 2)]),
+        Err(NodeError::InvalidParentIndex(2))
+    ));
+}
+
+#[test]
+fn children_data() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    for i in 1..=4 {
+        root.append_value(i, &mut arena);
+    }
+    let first = arena[root].first_child().unwrap();
+    first.append_value(10, &mut arena);
+    root.prepend(arena.new_node(-1), &mut arena);
+
+    assert_eq!(
+        root.children_data(&arena).copied().collect::<Vec<_>>(),
+        [-1, 1, 2, 3, 4]
+    );
+    assert_eq!(
+        root.children_data(&arena)
+            .rev()
+            .copied()
+            .collect::<Vec<_>>(),
+        [4, 3, 2, 1, -1]
+    );
+    assert_eq!(first.children_data(&arena).collect::<Vec<_>>(), [&10]);
+}
+
+#[test]
+fn is_balanced() {
+    // Complete binary tree of depth 3, balanced.
+    let (mut arena, root) = Arena::unfold(0, 3, |&depth| (depth, vec![depth + 1, depth + 1]));
+    assert!(root.is_balanced(&arena));
+
+    // Make one leaf one level deeper: still balanced.
+    let leaf = root.first_leaf(&arena);
+    let deeper = leaf.append_value(4, &mut arena);
+    assert!(root.is_balanced(&arena));
+
+    // Two levels deeper than the sibling leaf: unbalanced.
+    deeper.append_value(5, &mut arena);
+    assert!(!root.is_balanced(&arena));
+    assert!(!arena[leaf].parent().unwrap().is_balanced(&arena));
+    assert!(root.last_leaf(&arena).is_balanced(&arena));
+
+    // Imbalance between subtrees of the root.
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    arena.extend_chain(root, 1..=3);
+    root.append_value(10, &mut arena);
+    assert!(!root.is_balanced(&arena));
+
+    // A node with a single child is only balanced if the child is a leaf, so
+    // paths longer than two nodes are not balanced.
+    for len in 1..6 {
+        let mut arena = Arena::new();
+        let root = arena.new_node(0);
+        arena.extend_chain(root, 1..len);
+        assert_eq!(root.is_balanced(&arena), len <= 2, "path of {len}");
+    }
+}
+
+#[test]
+fn walk() {
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Enter(NodeId, &'static str, usize),
+        Leave(NodeId, &'static str, usize),
+    }
+
+    struct Recorder(Vec<Event>);
+
+    impl TreeVisitor<&'static str> for Recorder {
+        fn enter(&mut self, id: NodeId, data: &&'static str, depth: usize) {
+            self.0.push(Event::Enter(id, data, depth));
+        }
+
+        fn leave(&mut self, id: NodeId, data: &&'static str, depth: usize) {
+            self.0.push(Event::Leave(id, data, depth));
+        }
+    }
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   `-- 1_1_1
+    //     `-- 1_2
+
+    let mut recorder = Recorder(Vec::new());
+    n1.walk(&arena, &mut recorder);
+    assert_eq!(
+        recorder.0,
+        &[
+            Event::Enter(n1, "1", 0),
+            Event::Enter(n1_1, "1_1", 1),
+            Event::Enter(n1_1_1, "1_1_1", 2),
+            Event::Leave(n1_1_1, "1_1_1", 2),
+            Event::Leave(n1_1, "1_1", 1),
+            Event::Enter(n1_2, "1_2", 1),
+            Event::Leave(n1_2, "1_2", 1),
+            Event::Leave(n1, "1", 0),
+        ]
+    );
+
+    // Depth is relative to the starting node.
+    let mut recorder = Recorder(Vec::new());
+    n1_1.walk(&arena, &mut recorder);
+    assert_eq!(
+        recorder.0,
+        &[
+            Event::Enter(n1_1, "1_1", 0),
+            Event::Enter(n1_1_1, "1_1_1", 1),
+            Event::Leave(n1_1_1, "1_1_1", 1),
+            Event::Leave(n1_1, "1_1", 0),
+        ]
+    );
+}
+
+#[test]
+fn nodes_at_depth() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    n1_1_1.append_value("1_1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n1_3_1 = n1_3.append_value("1_3_1", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   |-- 1_1_1
+    //     |   |   `-- 1_1_1_1
+    //     |   `-- 1_1_2
+    //     |-- 1_2
+    //     `-- 1_3
+    //         `-- 1_3_1
+
+    assert_eq!(n1.nodes_at_depth(&arena, 0).collect::<Vec<_>>(), &[n1]);
+    assert_eq!(
+        n1.nodes_at_depth(&arena, 1).collect::<Vec<_>>(),
+        &[n1_1, n1_2, n1_3]
+    );
+    assert_eq!(
+        n1.nodes_at_depth(&arena, 2).collect::<Vec<_>>(),
+        &[n1_1_1, n1_1_2, n1_3_1]
+    );
+    assert_eq!(n1.nodes_at_depth(&arena, 4).count(), 0);
+    assert_eq!(
+        n1_1.nodes_at_depth(&arena, 1).collect::<Vec<_>>(),
+        &[n1_1_1, n1_1_2]
+    );
+}
+
+#[test]
+fn children_data_mut() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(0);
+    let n1_1 = arena.new_node(0);
+    let n1_2 = arena.new_node(0);
+    let n1_3 = arena.new_node(0);
+    // Children order differs from storage order.
+    n1.append(n1_3, &mut arena);
+    n1.append(n1_1, &mut arena);
+    n1.append(n1_2, &mut arena);
+    n1_1.append_value(0, &mut arena);
+
+    let mut children = arena.children_data_mut(n1);
+    for (i, data) in children.iter_mut().enumerate() {
+        **data = i + 1;
+    }
+    let [first, .., last] = children.as_mut_slice() else {
+        panic!("expected at least two children");
+    };
+    core::mem::swap(*first, *last);
+
+    assert_eq!(*arena[n1_3].get(), 3);
+    assert_eq!(*arena[n1_1].get(), 2);
+    assert_eq!(*arena[n1_2].get(), 1);
+    assert_eq!(*arena[n1].get(), 0);
+
+    let leaf = arena[n1_1].first_child().unwrap();
+    assert!(arena.children_data_mut(leaf).is_empty());
+}
+
+#[test]
+fn preorder_index() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n2 = arena.new_node("2");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // |-- 1
+    // |   |-- 1_1
+    // |   `-- 1_2
+    // `-- 2
+    //     `-- 2_1
+
+    assert_eq!(n1.preorder_index(&arena), 0);
+    assert_eq!(n1_1.preorder_index(&arena), 1);
+    for (position, id) in arena.iter_tree_order().enumerate() {
+        assert_eq!(id.preorder_index(&arena), position);
+    }
+    assert_eq!(n1_2.preorder_index(&arena), 2);
+    assert_eq!(n2_1.preorder_index(&arena), 4);
+
+    n1_1.remove(&mut arena);
+    assert_eq!(n1_2.preorder_index(&arena), 1);
+}
+
+#[test]
+fn descendants_data_mut() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(String::from("1"));
+    let n1_2 = arena.new_node(String::from("1_2"));
+    let n1_1 = arena.new_node(String::from("1_1"));
+    n1.append(n1_1, &mut arena);
+    n1.append(n1_2, &mut arena);
+    let n1_1_1 = n1_1.append_value(String::from("1_1_1"), &mut arena);
+    let n2 = arena.new_node(String::from("2"));
+    // arena
+    // |-- 1
+    // |   |-- 1_1
+    // |   |   `-- 1_1_1
+    // |   `-- 1_2
+    // `-- 2
+
+    let mut visited = Vec::new();
+    for (i, data) in n1.descendants_data_mut(&mut arena).enumerate() {
+        visited.push(data.clone());
+        data.push_str(&format!("#{}", i));
+    }
+    assert_eq!(visited, &["1", "1_1", "1_1_1", "1_2"]);
+    assert_eq!(arena[n1].get(), "1#0");
+    assert_eq!(arena[n1_1].get(), "1_1#1");
+    assert_eq!(arena[n1_1_1].get(), "1_1_1#2");
+    assert_eq!(arena[n1_2].get(), "1_2#3");
+    assert_eq!(arena[n2].get(), "2");
+
+    // The siblings of the subtree root are not visited.
+    let visited = n1_1
+        .descendants_data_mut(&mut arena)
+        .map(|data| data.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(visited, &["1_1#1", "1_1_1#2"]);
+
+    // Deeper trees are visited as by `descendants`, and lazily.
+    let n1_1_1_1 = n1_1_1.append_value(String::from("1_1_1_1"), &mut arena);
+    n1_1_1_1.append_value(String::from("1_1_1_1_1"), &mut arena);
+    n1_2.append_value(String::from("1_2_1"), &mut arena);
+    n1_1.append_value(String::from("1_1_2"), &mut arena);
+    let expected = n1
+        .descendants(&arena)
+        .map(|id| arena[id].get().clone())
+        .collect::<Vec<_>>();
+    let visited = n1
+        .descendants_data_mut(&mut arena)
+        .map(|data| data.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(visited, expected);
+    if let Some(data) = n1.descendants_data_mut(&mut arena).next() {
+        data.push('!');
+    }
+    assert_eq!(arena[n1].get(), "1#0!");
+}
+
+#[test]
+fn ancestor_at_depth() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_1_1 = n1_1_1.append_value("1_1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   `-- 1_1_1
+    //     |       `-- 1_1_1_1
+    //     `-- 1_2
+
+    assert_eq!(n1_1_1_1.ancestor_at_depth(&arena, 0), Some(n1));
+    assert_eq!(n1_1_1_1.ancestor_at_depth(&arena, 1), Some(n1_1));
+    assert_eq!(n1_1_1_1.ancestor_at_depth(&arena, 2), Some(n1_1_1));
+    assert_eq!(n1_1_1_1.ancestor_at_depth(&arena, 3), Some(n1_1_1_1));
+    assert_eq!(n1_2.ancestor_at_depth(&arena, 0), Some(n1));
+    assert_eq!(n1_2.ancestor_at_depth(&arena, 2), None);
+    assert_eq!(n1.ancestor_at_depth(&arena, 0), Some(n1));
+}
+
+#[test]
+fn cmp_preorder() {
+    let mut arena = Arena::new();
+    let n2 = arena.new_node("2");
+    let n1 = arena.new_node("1");
+    let n1_2 = arena.new_node("1_2");
+    let n1_1 = arena.new_node("1_1");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    n1.append(n1_2, &mut arena);
+    n1_2.insert_before(n1_1, &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    // arena
+    // |-- 2
+    // |   `-- 2_1
+    // `-- 1
+    //     |-- 1_1
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     `-- 1_2
+    //         `-- 1_2_1
+
+    let mut ids = vec![n1_2_1, n1_1_2, n1, n2_1, n1_1, n1_2, n2, n1_1_1];
+    ids.sort_by(|&a, &b| arena.cmp_preorder(a, b));
+    assert_eq!(ids, arena.iter_tree_order().collect::<Vec<_>>());
+    assert_eq!(ids, &[n2, n2_1, n1, n1_1, n1_1_1, n1_1_2, n1_2, n1_2_1]);
+    assert_eq!(arena.cmp_preorder(n1_1, n1_1), core::cmp::Ordering::Equal);
+}
+
+#[test]
+fn accumulate_subtree() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    n1_1_2.append_value("1_1_2_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     |       `-- 1_1_2_1
+    //     `-- 1_2
+
+    let count = |_: &&str, children: &[usize]| 1 + children.iter().sum::<usize>();
+    assert_eq!(n1.accumulate_subtree(&arena, count), 6);
+    assert_eq!(n1_1.accumulate_subtree(&arena, count), 4);
+    assert_eq!(n1_1_2.accumulate_subtree(&arena, count), 2);
+    assert_eq!(n1_2.accumulate_subtree(&arena, count), 1);
+
+    // Children results are passed in order.
+    let render = n1.accumulate_subtree(&arena, |data, children: &[String]| {
+        if children.is_empty() {
+            data.to_string()
+        } else {
+            format!("{}({})", data, children.join(","))
+        }
+    });
+    assert_eq!(render, "1(1_1(1_1_1,1_1_2(1_1_2_1)),1_2)");
+}
+
+#[test]
+fn tree_summary() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    n1_1.append_value("1_1_2", &mut arena);
+    n1_1.append_value("1_1_3", &mut arena);
+    n1_1.append_value("1_1_4", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   |-- 1_1_1
+    //     |   |-- 1_1_2
+    //     |   |-- 1_1_3
+    //     |   `-- 1_1_4
+    //     `-- 1_2
+
+    assert_eq!(
+        arena.tree_summary(n1),
+        TreeSummary {
+            node_count: 7,
+            leaf_count: 5,
+            height: 2,
+            max_branching: 4,
+            is_balanced: true,
+        }
+    );
+
+    n1_1_1.append_value("1_1_1_1", &mut arena);
+    let summary = arena.tree_summary(n1);
+    assert_eq!(summary.node_count, 8);
+    assert_eq!(summary.leaf_count, 5);
+    assert_eq!(summary.height, 3);
+    assert!(!summary.is_balanced);
+    assert_eq!(summary.is_balanced, n1.is_balanced(&arena));
+
+    assert_eq!(
+        arena.tree_summary(n1_2),
+        TreeSummary {
+            node_count: 1,
+            leaf_count: 1,
+            height: 0,
+            max_branching: 0,
+            is_balanced: true,
+        }
+    );
+
+    // Chains longer than two nodes are not balanced.
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    arena.extend_chain(root, 1..4);
+    assert_eq!(
+        arena.tree_summary(root),
+        TreeSummary {
+            node_count: 4,
+            leaf_count: 1,
+            height: 3,
+            max_branching: 1,
+            is_balanced: false,
+        }
+    );
+    assert_eq!(
+        arena.tree_summary(root).is_balanced,
+        root.is_balanced(&arena)
+    );
+}
+
+#[test]
+fn children_vec() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(1);
+    let n1_1 = n1.append_value(2, &mut arena);
+    let n1_2 = n1.append_value(3, &mut arena);
+    let n1_3 = n1.append_value(4, &mut arena);
+
+    let children = n1.children_vec(&arena);
+    assert_eq!(children, n1.children(&arena).collect::<Vec<_>>());
+    assert_eq!(children, &[n1_1, n1_2, n1_3]);
+
+    // The snapshot can be used while mutating the arena.
+    for child in children {
+        let value = *arena[child].get();
+        child.append_value(value * 10, &mut arena);
+    }
+    n1_2.detach(&mut arena);
+    assert_eq!(n1.children_vec(&arena), &[n1_1, n1_3]);
+    assert_eq!(arena.count(), 7);
+    assert!(n1_1.children_vec(&arena).len() == 1);
+}
+
+#[test]
+fn zip_breadth_first() {
+    let mut arena = Arena::new();
+    let a = arena.new_node("a");
+    let a_1 = a.append_value("a_1", &mut arena);
+    let a_2 = a.append_value("a_2", &mut arena);
+    let a_1_1 = a_1.append_value("a_1_1", &mut arena);
+    let b = arena.new_node("b");
+    let b_1 = b.append_value("b_1", &mut arena);
+    let b_1_1 = b_1.append_value("b_1_1", &mut arena);
+    let b_1_2 = b_1.append_value("b_1_2", &mut arena);
+    let b_1_3 = b_1.append_value("b_1_3", &mut arena);
+    // arena
+    // |-- a
+    // |   |-- a_1
+    // |   |   `-- a_1_1
+    // |   `-- a_2
+    // `-- b
+    //     `-- b_1
+    //         |-- b_1_1
+    //         |-- b_1_2
+    //         `-- b_1_3
+
+    assert_eq!(
+        indextree::zip_breadth_first(&arena, a, b).collect::<Vec<_>>(),
+        &[
+            (Some(a), Some(b)),
+            (Some(a_1), Some(b_1)),
+            (Some(a_2), Some(b_1_1)),
+            (Some(a_1_1), Some(b_1_2)),
+            (None, Some(b_1_3)),
+        ]
+    );
+    assert_eq!(
+        indextree::zip_breadth_first(&arena, a_1, b_1_3).collect::<Vec<_>>(),
+        &[(Some(a_1), Some(b_1_3)), (Some(a_1_1), None)]
+    );
+}
+
+#[test]
+fn set_data() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(1);
+    let n2 = arena.new_node(2);
+    assert_eq!(arena.set_data(n1, 10).unwrap(), 1);
+    assert_eq!(*arena[n1].get(), 10);
+    assert_eq!(*arena[n2].get(), 2);
+
+    n2.remove(&mut arena);
+    assert!(matches!(
+        arena.set_data(n2, 20),
+        Err(NodeError::RemovedNode(id)) if id == n2
+    ));
+
+    // The slot was reused by another node.
+    let n3 = arena.new_node(3);
+    assert!(matches!(
+        arena.set_data(n2, 20),
+        Err(NodeError::RemovedNode(_))
+    ));
+    assert_eq!(arena.set_data(n3, 30).unwrap(), 3);
+
+    let mut other = Arena::new();
+    other.new_node(0);
+    assert!(matches!(
+        other.set_data(n3, 0),
+        Err(NodeError::NotFound(id)) if id == n3
+    ));
+    assert!(arena.set_data(n3, 0).is_ok_and(|old| old == 30));
+
+    // Ids from before the arena was cleared are rejected.
+    arena.clear();
+    let n4 = arena.new_node(4);
+    assert_eq!(n4, n1);
+    assert!(matches!(
+        arena.set_data(n1, 10),
+        Err(NodeError::NotFound(id)) if id == n1
+    ));
+    assert_eq!(*arena[n4].get(), 4);
+    assert_eq!(arena.set_data(n4, 40).unwrap(), 4);
+}
+
+#[test]
+fn traverse_indexed() {
+    use indextree::NodeEdge;
+
+    let mut arena = Arena::new();
+    let n0 = arena.new_node("0");
+    let n1 = arena.new_node("1");
+    n0.insert_after(n1, &mut arena);
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    // arena
+    // |-- 0
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   `-- 1_2_2
+    //     `-- 1_3
+
+    let edges = n1.traverse_indexed(&arena).collect::<Vec<_>>();
+    assert_eq!(
+        edges.iter().map(|&(edge, _)| edge).collect::<Vec<_>>(),
+        n1.traverse(&arena).collect::<Vec<_>>()
+    );
+    for (id, index) in [
+        (n1, 1),
+        (n1_1, 0),
+        (n1_2, 1),
+        (n1_3, 2),
+        (n1_2_1, 0),
+        (n1_2_2, 1),
+    ] {
+        assert!(edges.contains(&(NodeEdge::Start(id), index)));
+        assert!(edges.contains(&(NodeEdge::End(id), index)));
+    }
+
+    assert_eq!(
+        n1_2.traverse_indexed(&arena).collect::<Vec<_>>(),
+        &[
+            (NodeEdge::Start(n1_2), 1),
+            (NodeEdge::Start(n1_2_1), 0),
+            (NodeEdge::End(n1_2_1), 0),
+            (NodeEdge::Start(n1_2_2), 1),
+            (NodeEdge::End(n1_2_2), 1),
+            (NodeEdge::End(n1_2), 1),
+        ]
+    );
+}
+
+#[test]
+fn map_data_in_place() {
+    let mut arena = Arena::new();
+    let ids = (0..5).map(|i| arena.new_node(i)).collect::<Vec<_>>();
+    ids[0].append(ids[1], &mut arena);
+    ids[1].remove(&mut arena);
+    ids[3].remove(&mut arena);
+
+    let mut visited = 0;
+    arena.map_data_in_place(|data| {
+        *data += 100;
+        visited += 1;
+    });
+    assert_eq!(visited, 3);
+    assert_eq!(
+        arena
+            .iter()
+            .filter(|node| !node.is_removed())
+            .map(|node| *node.get())
+            .collect::<Vec<_>>(),
+        &[100, 102, 104]
+    );
+    assert!(ids[1].is_removed(&arena));
+    assert!(ids[3].is_removed(&arena));
+}
+
+#[test]
+fn new_node_with_child() {
+    let mut arena = Arena::new();
+    let (parent, child) = arena.new_node_with_child(1, 2);
+    assert_eq!(arena[child].parent(), Some(parent));
+    assert_eq!(parent.children(&arena).collect::<Vec<_>>(), &[child]);
+    assert!(arena[parent].parent().is_none());
+    assert_eq!(*arena[parent].get(), 1);
+    assert_eq!(*arena[child].get(), 2);
+}
+
+#[test]
+fn debug_assert_valid() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    n1.debug_assert_valid(&arena);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "NodeId 2 stamp 0 does not match arena (live stamp -1)")]
+fn debug_assert_valid_removed() {
+    let mut arena = Arena::new();
+    arena.new_node("1");
+    let n2 = arena.new_node("2");
+    n2.remove(&mut arena);
+    n2.debug_assert_valid(&arena);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "NodeId 2 is out of range of the arena (1 nodes)")]
+fn debug_assert_valid_out_of_range() {
+    let mut arena = Arena::new();
+    arena.new_node("1");
+    let n2 = arena.new_node("2");
+    arena.clear();
+    arena.new_node("1");
+    n2.debug_assert_valid(&arena);
+}
+
+#[test]
+fn levels() {
+    let (arena, root) = Arena::unfold(3, 3, |&n| (n, (0..n).map(|i| i + 1).collect()));
+    let levels = root.levels(&arena);
+    assert_eq!(levels.len(), 4);
+    assert_eq!(levels[0], &[root]);
+    assert_eq!(
+        levels.iter().map(Vec::len).sum::<usize>(),
+        root.descendants(&arena).count()
+    );
+    for (depth, level) in levels.iter().enumerate() {
+        assert!(!level.is_empty());
+        assert_eq!(
+            *level,
+            root.nodes_at_depth(&arena, depth).collect::<Vec<_>>()
+        );
+    }
+
+    let mut arena = Arena::new();
+    let leaf = arena.new_node(0);
+    assert_eq!(leaf.levels(&arena), vec![vec![leaf]]);
+}
+
+#[test]
+fn path_between() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n2 = arena.new_node("2");
+    // arena
+    // |-- 1
+    // |   |-- 1_1
+    // |   |   `-- 1_1_1
+    // |   `-- 1_2
+    // |       `-- 1_2_1
+    // |           `-- 1_2_1_1
+    // `-- 2
+
+    // Cousins meet at their grandparent exactly once.
+    let path = n1_1_1.path_between(n1_2_1, &arena).unwrap();
+    assert_eq!(path, &[n1_1_1, n1_1, n1, n1_2, n1_2_1]);
+    assert_eq!(path.iter().filter(|&&id| id == n1).count(), 1);
+
+    let mut reversed = n1_2_1.path_between(n1_1_1, &arena).unwrap();
+    reversed.reverse();
+    assert_eq!(reversed, path);
+
+    assert_eq!(
+        n1_2_1_1.path_between(n1_2, &arena),
+        Some(vec![n1_2_1_1, n1_2_1, n1_2])
+    );
+    assert_eq!(n1_1.path_between(n1_1, &arena), Some(vec![n1_1]));
+    assert_eq!(n1_1.path_between(n2, &arena), None);
+}
+
+#[test]
+fn group_isomorphic_subtrees() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(0);
+    let a = root.append_value(1, &mut arena);
+    let a_1 = a.append_value(2, &mut arena);
+    let a_2 = a.append_value(3, &mut arena);
+    let b = root.append_value(1, &mut arena);
+    let b_1 = b.append_value(2, &mut arena);
+    let b_2 = b.append_value(3, &mut arena);
+    // Same data, children in a different order.
+    let c = root.append_value(1, &mut arena);
+    let c_1 = c.append_value(3, &mut arena);
+    let c_2 = c.append_value(2, &mut arena);
+    // Another tree with an equal subtree.
+    let other = arena.new_node(1);
+    let other_1 = other.append_value(2, &mut arena);
+    let other_2 = other.append_value(3, &mut arena);
+    let removed = arena.new_node(1);
+    removed.remove(&mut arena);
+
+    let groups = arena.group_isomorphic_subtrees();
+    assert_eq!(
+        groups,
+        vec![
+            vec![root],
+            vec![a, b, other],
+            vec![a_1, b_1, c_2, other_1],
+            vec![a_2, b_2, c_1, other_2],
+            vec![c],
+        ]
+    );
+    assert_eq!(
+        groups.iter().map(Vec::len).sum::<usize>(),
+        arena.iter_tree_order().count()
+    );
+}
+
+#[test]
+fn collect_children_into() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(1);
+    let n1_1 = n1.append_value(2, &mut arena);
+    let n1_2 = n1.append_value(3, &mut arena);
+    let n1_1_1 = n1_1.append_value(4, &mut arena);
+
+    let mut buf = vec![n1_2, n1_2, n1_2];
+    n1.collect_children_into(&arena, &mut buf);
+    assert_eq!(buf, n1.children_vec(&arena));
+
+    n1_1.collect_children_into(&arena, &mut buf);
+    assert_eq!(buf, &[n1_1_1]);
+    n1_1_1.remove(&mut arena);
+    n1_1.collect_children_into(&arena, &mut buf);
+    assert!(buf.is_empty());
+
+    // The buffer is reused while mutating the arena.
+    n1.collect_children_into(&arena, &mut buf);
+    for &child in &buf {
+        child.append_value(0, &mut arena);
+    }
+    assert_eq!(n1.descendants(&arena).count(), 5);
+}
+
+#[test]
+fn descendants_by_cost() {
+    let mut arena = Arena::new();
+    let root = arena.new_node(("root", 0));
+    let shallow = root.append_value(("shallow", 10), &mut arena);
+    let deep = root.append_value(("deep", 1), &mut arena);
+    let deep_1 = deep.append_value(("deep_1", 2), &mut arena);
+    let deep_1_1 = deep_1.append_value(("deep_1_1", 3), &mut arena);
+    let deep_1_1_1 = deep_1_1.append_value(("deep_1_1_1", 5), &mut arena);
+    let shallow_1 = shallow.append_value(("shallow_1", 0), &mut arena);
+    // arena
+    // `-- root (0)
+    //     |-- shallow (10)
+    //     |   `-- shallow_1 (0)
+    //     `-- deep (1)
+    //         `-- deep_1 (2)
+    //             `-- deep_1_1 (3)
+    //                 `-- deep_1_1_1 (5)
+
+    let order = root
+        .descendants_by_cost(&arena, |&(_, cost)| cost)
+        .collect::<Vec<_>>();
+    // Cumulative costs: root 0, deep 1, deep_1 3, deep_1_1 6, shallow 10,
+    // shallow_1 10, deep_1_1_1 11.
+    assert_eq!(
+        order,
+        &[root, deep, deep_1, deep_1_1, shallow, shallow_1, deep_1_1_1]
+    );
+    assert_eq!(order.len(), root.descendants(&arena).count());
+
+    // Uniform costs visit the tree level by level.
+    assert_eq!(
+        root.descendants_by_cost(&arena, |_| 1).collect::<Vec<_>>(),
+        &[root, shallow, deep, shallow_1, deep_1, deep_1_1, deep_1_1_1]
+    );
+}
+
+#[test]
+fn record_ops() {
+    use indextree::Op;
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = arena.new_node("1_1");
+    let n1_2 = arena.new_node("1_2");
+    let n1_3 = arena.new_node("1_3");
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+
+    let mut recorder = arena.record_ops();
+    n1.append(n1_2, &mut recorder);
+    n1.prepend(n1_1, &mut recorder);
+    n1_2.insert_after(n1_3, &mut recorder);
+    n1_2.insert_before(n2, &mut recorder);
+    assert!(n1.checked_append(n1, &mut recorder).is_err());
+    n2.remove(&mut recorder);
+    n1_3.detach(&mut recorder);
+    n1_1.remove_subtree(&mut recorder);
+    assert_eq!(
+        recorder.ops(),
+        &[
+            Op::Append {
+                parent: n1,
+                child: n1_2
+            },
+            Op::Prepend {
+                parent: n1,
+                child: n1_1
+            },
+            Op::InsertAfter {
+                node: n1_2,
+                sibling: n1_3
+            },
+            Op::InsertBefore {
+                node: n1_2,
+                sibling: n2
+            },
+            Op::Remove(n2),
+            Op::Detach(n1_3),
+            Op::RemoveSubtree(n1_1),
+        ]
+    );
+
+    assert_eq!(recorder.take_ops().len(), 7);
+    let n1_3_1 = n1_3.append_value("1_3_1", &mut recorder);
+    n1.append(n1_3, &mut recorder);
+    // Changes leaving the tree as is are not recorded.
+    n1.append(n1_3, &mut recorder);
+    assert!(n1.checked_prepend(n2_1, &mut recorder).is_ok());
+    n1_3.insert_before(n1_2, &mut recorder);
+    n1_2.insert_after(n1_3, &mut recorder);
+    assert_eq!(
+        recorder.take_ops(),
+        &[
+            Op::Append {
+                parent: n1_3,
+                child: n1_3_1
+            },
+            Op::Append {
+                parent: n1,
+                child: n1_3
+            }
+        ]
+    );
+    drop(recorder);
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n2_1, n1_2, n1_3]);
+
+    // Nothing is recorded once the recorder is dropped.
+    n1_3.detach(&mut arena);
+    assert!(arena.record_ops().ops().is_empty());
+
+    // Splicing removals and moves to another arena are recorded as well.
+    let mut recorder = arena.record_ops();
+    n1_2.remove_splice_at_end(&mut recorder);
+    let (moved, root) = n2_1.move_into_new_arena(&mut recorder);
+    assert_eq!(*moved[root].get(), "2_1");
+    assert_eq!(recorder.ops(), &[Op::Remove(n1_2), Op::RemoveSubtree(n2_1)]);
+
+    // The log is not part of the equality of arenas.
+    let snapshot = recorder.clone();
+    assert!(!recorder.ops().is_empty());
+    assert_eq!(*recorder, snapshot);
+}
+
+/// Replays the operations on `replica`, creating the nodes they refer to
+/// when needed.
+fn replay_ops<T: Default>(ops: &[indextree::Op], replica: &mut Arena<T>) {
+    use indextree::Op;
+
+    let ensure = |id: NodeId, replica: &mut Arena<T>| {
+        if replica.get_node_id_at(id.into()) != Some(id) {
+            assert_eq!(replica.new_node(T::default()), id);
+        }
+    };
+    for &op in ops {
+        match op {
+            Op::Append { parent, child } => {
+                ensure(parent, replica);
+                ensure(child, replica);
+                parent.append(child, replica);
+            }
+            Op::Prepend { parent, child } => parent.prepend(child, replica),
+            Op::InsertAfter { node, sibling } => node.insert_after(sibling, replica),
+            Op::InsertBefore { node, sibling } => node.insert_before(sibling, replica),
+            Op::Detach(id) => id.detach(replica),
+            Op::Remove(id) => id.remove(replica),
+            Op::RemoveSubtree(id) => id.remove_subtree(replica),
+        }
+    }
+}
+
+/// Applies `mutate` to the arena while recording, and checks that replaying
+/// the recorded operations on a copy leads to the same structure.
+fn assert_replayable<F>(arena: &mut Arena<&'static str>, mutate: F) -> Vec<indextree::Op>
+where
+    F: FnOnce(&mut Arena<&'static str>),
+{
+    let mut replica = arena.clone();
+    let mut recorder = arena.record_ops();
+    mutate(&mut recorder);
+    let ops = recorder.take_ops();
+    drop(recorder);
+
+    replay_ops(&ops, &mut replica);
+    assert_eq!(replica.count(), arena.count());
+    for (expected, node) in arena.iter().zip(replica.iter()) {
+        assert_eq!(node.is_removed(), expected.is_removed());
+        if !node.is_removed() {
+            assert_eq!(node.parent(), expected.parent());
+            assert_eq!(node.previous_sibling(), expected.previous_sibling());
+            assert_eq!(node.next_sibling(), expected.next_sibling());
+            assert_eq!(node.first_child(), expected.first_child());
+            assert_eq!(node.last_child(), expected.last_child());
+        }
+    }
+    ops
+}
+
+#[test]
+fn record_ops_replay() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    let a = root.append_value("a", &mut arena);
+    let b = root.append_value("b", &mut arena);
+    let c = root.append_value("c", &mut arena);
+    let d = root.append_value("d", &mut arena);
+    let r1 = arena.new_node("r1");
+    let r2 = arena.new_node("r2");
+    let r3 = arena.new_node("r3");
+    r1.insert_after(r2, &mut arena);
+    r2.insert_after(r3, &mut arena);
+
+    assert_replayable(&mut arena, |arena| {
+        root.append_value("e", arena);
+    });
+    assert_replayable(&mut arena, |arena| {
+        a.append_default(arena);
+    });
+    assert_replayable(&mut arena, |arena| {
+        let (parent, _) = arena.new_node_with_child("f", "f_1");
+        b.append(parent, arena);
+    });
+    assert_replayable(&mut arena, |arena| {
+        arena.extend_chain(c, ["c_1", "c_2"]);
+    });
+
+    // Non-adjacent, adjacent, and with the first child.
+    assert_replayable(&mut arena, |arena| a.swap_siblings(d, arena).unwrap());
+    assert_replayable(&mut arena, |arena| b.swap_siblings(c, arena).unwrap());
+    assert_eq!(root.children(&arena).next(), Some(d));
+    assert_replayable(&mut arena, |arena| b.swap_siblings(d, arena).unwrap());
+    assert_replayable(&mut arena, |arena| r1.swap_siblings(r3, arena).unwrap());
+    assert_replayable(&mut arena, |arena| r2.swap_siblings(r3, arena).unwrap());
+
+    let reversed = root.children(&arena).rev().collect::<Vec<_>>();
+    assert_replayable(&mut arena, |arena| {
+        root.reorder_children(&reversed, arena).unwrap()
+    });
+    assert!(assert_replayable(&mut arena, |arena| {
+        root.reorder_children(&reversed, arena).unwrap()
+    })
+    .is_empty());
+
+    assert_replayable(&mut arena, |arena| root.prepend(a, arena));
+    assert_replayable(&mut arena, |arena| b.insert_after(r1, arena));
+    assert_replayable(&mut arena, |arena| b.insert_before(r2, arena));
+    assert_replayable(&mut arena, |arena| {
+        c.insert_between(a, arena[a].next_sibling().unwrap(), arena)
+            .unwrap()
+    });
+    assert_replayable(&mut arena, |arena| a.move_after(d, arena).unwrap());
+    assert_replayable(&mut arena, |arena| {
+        c.make_root(arena);
+    });
+    assert_replayable(&mut arena, |arena| c.remove(arena));
+    assert_replayable(&mut arena, |arena| b.remove_splice_at_end(arena));
+    assert_replayable(&mut arena, |arena| {
+        d.move_into_new_arena(arena);
+    });
+    assert_replayable(&mut arena, |arena| root.remove_subtree(arena));
+}
+
+#[test]
+fn same_tree() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    // arena
+    // |-- 1
+    // |   |-- 1_1
+    // |   |   `-- 1_1_1
+    // |   `-- 1_2
+    // `-- 2
+    //     `-- 2_1
+
+    assert!(n1_1_1.same_tree(n1_2, &arena));
+    assert!(n1_2.same_tree(n1_1_1, &arena));
+    assert!(n1.same_tree(n1, &arena));
+    assert!(!n1_1_1.same_tree(n2_1, &arena));
+    assert!(!n1.same_tree(n2, &arena));
+
+    n1_1.detach(&mut arena);
+    assert!(!n1_1_1.same_tree(n1_2, &arena));
+    n2_1.append(n1_1, &mut arena);
+    assert!(n1_1_1.same_tree(n2, &arena));
+}
+
+#[test]
+fn leaf_runs() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n1_3_1 = n1_3.append_value("1_3_1", &mut arena);
+    let n1_3_2 = n1_3.append_value("1_3_2", &mut arena);
+    let n1_3_2_1 = n1_3_2.append_value("1_3_2_1", &mut arena);
+    let n1_3_3 = n1_3.append_value("1_3_3", &mut arena);
+    let n1_4 = n1.append_value("1_4", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |-- 1_3
+    //     |   |-- 1_3_1
+    //     |   |-- 1_3_2
+    //     |   |   `-- 1_3_2_1
+    //     |   `-- 1_3_3
+    //     `-- 1_4
+
+    assert_eq!(
+        n1.leaf_runs(&arena).collect::<Vec<_>>(),
+        vec![vec![n1_1, n1_2], vec![n1_3_1], vec![n1_3_2_1, n1_3_3, n1_4],]
+    );
+    assert_eq!(
+        n1_3_2.leaf_runs(&arena).collect::<Vec<_>>(),
+        vec![vec![n1_3_2_1]]
+    );
+    assert_eq!(n1_4.leaf_runs(&arena).collect::<Vec<_>>(), vec![vec![n1_4]]);
+}
+
+#[test]
+fn range() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n2 = arena.new_node("2");
+
+    let contains =
+        |outer: (usize, usize), inner: (usize, usize)| outer.0 < inner.0 && inner.1 < outer.1;
+    for child in [n1_1, n1_2, n1_3] {
+        assert!(contains(n1.range(&arena), child.range(&arena)));
+        assert!(!contains(child.range(&arena), n1.range(&arena)));
+    }
+    assert!(contains(n1_1.range(&arena), n1_1_1.range(&arena)));
+    assert!(n1_1.range(&arena).1 < n1_2.range(&arena).0);
+    assert!(n1.range(&arena).1 < n2.range(&arena).0);
+    assert_eq!(n1_1_1.range(&arena), (2, 3));
+}
+
+#[test]
+fn freeze() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let removed = n1.append_value("removed", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    removed.remove(&mut arena);
+
+    let order = arena.iter_tree_order().collect::<Vec<_>>();
+    let descendants = n1.descendants(&arena).collect::<Vec<_>>();
+    let children = n1.children(&arena).collect::<Vec<_>>();
+
+    let frozen: FrozenArena<_> = arena.freeze();
+    assert_eq!(frozen.count(), 6);
+    assert_eq!(
+        frozen
+            .roots()
+            .flat_map(|root| frozen.descendants(root))
+            .collect::<Vec<_>>(),
+        order
+    );
+    assert_eq!(frozen.descendants(n1).collect::<Vec<_>>(), descendants);
+    assert_eq!(frozen.children(n1).collect::<Vec<_>>(), children);
+    assert_eq!(frozen.roots().collect::<Vec<_>>(), &[n1, n2]);
+    assert_eq!(frozen.parent(n1_1_1), Some(n1_1));
+    assert_eq!(frozen.parent(n1), None);
+    assert_eq!(frozen.get(n2_1), Some(&"2_1"));
+
+    assert!(frozen.is_descendant(n1, n1_1_1));
+    assert!(frozen.is_descendant(n1_1, n1_1_1));
+    assert!(!frozen.is_descendant(n1_2, n1_1_1));
+    assert!(!frozen.is_descendant(n1, n2_1));
+    assert!(!frozen.is_descendant(n1, removed));
+    assert_eq!(frozen.get(removed), None);
+    assert_eq!(frozen.children(removed).count(), 0);
+}
+
+#[test]
+fn descendants_snapshot() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+
+    let mut visited = Vec::new();
+    for id in n1.descendants_snapshot(&arena) {
+        visited.push(id);
+        id.remove(&mut arena);
+    }
+    assert_eq!(visited, &[n1, n1_1, n1_1_1, n1_2, n1_2_1, n1_2_2]);
+    assert!(visited.iter().all(|id| id.is_removed(&arena)));
+    assert_eq!(arena.iter().filter(|node| !node.is_removed()).count(), 0);
+}
+
+#[test]
+fn common_ancestor() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_2_3 = n1_2.append_value("1_2_3", &mut arena);
+    let n1_2_3_1 = n1_2_3.append_value("1_2_3_1", &mut arena);
+    let n2 = arena.new_node("2");
+
+    assert_eq!(
+        arena.common_ancestor(&[n1_2_1_1, n1_2_2, n1_2_3_1]),
+        Some(n1_2)
+    );
+    assert_eq!(arena.common_ancestor(&[n1_2_1_1, n1_2_3_1, n1_1]), Some(n1));
+    assert_eq!(arena.common_ancestor(&[n1_2_3_1]), Some(n1_2_3_1));
+    assert_eq!(arena.common_ancestor(&[n1_2_1_1, n2]), None);
+    assert_eq!(arena.common_ancestor(&[]), None);
+}
+
+#[test]
+fn subtree_eq_fast() {
+    use std::cell::Cell;
+
+    /// Data counting how many times it is compared.
+    struct Counted<'a> {
+        value: usize,
+        comparisons: &'a Cell<usize>,
+    }
+
+    impl PartialEq for Counted<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.comparisons.set(self.comparisons.get() + 1);
+            self.value == other.value
+        }
+    }
+
+    let comparisons = Cell::new(0);
+    let mut arena = Arena::new();
+    let mut build = |children: usize| {
+        let root = arena.new_node(Counted {
+            value: 0,
+            comparisons: &comparisons,
+        });
+        for value in 0..children {
+            let child = arena.new_node(Counted {
+                value,
+                comparisons: &comparisons,
+            });
+            root.append(child, &mut arena);
+            for value in 0..100 {
+                let grandchild = arena.new_node(Counted {
+                    value,
+                    comparisons: &comparisons,
+                });
+                child.append(grandchild, &mut arena);
+            }
+        }
+        root
+    };
+    let a = build(10);
+    let b = build(10);
+    let c = build(11);
+
+    assert!(a.subtree_eq_fast(b, &arena));
+    assert_eq!(comparisons.get(), 1 + 10 + 10 * 100);
+
+    comparisons.set(0);
+    assert!(!a.subtree_eq_fast(c, &arena));
+    assert_eq!(comparisons.get(), 0);
+}
+
+#[test]
+fn to_sexpr() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    root.append_value("child1", &mut arena);
+    let n2 = root.append_value("child2", &mut arena);
+    n2.append_value("grandchild", &mut arena);
+    let n3 = root.append_value("a (b)\\c", &mut arena);
+    n3.append_value("", &mut arena);
+
+    assert_eq!(
+        root.to_sexpr(&arena),
+        r"(root (child1) (child2 (grandchild)) (a\ \(b\)\\c ()))"
+    );
+    assert_eq!(n2.to_sexpr(&arena), "(child2 (grandchild))");
+}
+
+#[test]
+fn from_sexpr() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("root");
+    root.append_value("child1", &mut arena);
+    let n2 = root.append_value("child 2", &mut arena);
+    n2.append_value("(grand\\child)", &mut arena);
+    n2.append_value("", &mut arena);
+
+    let sexpr = root.to_sexpr(&arena);
+    let (parsed, parsed_root) = Arena::from_sexpr(&sexpr).unwrap();
+    assert_eq!(parsed.count(), arena.count());
+    assert_eq!(parsed_root.to_sexpr(&parsed), sexpr);
+    assert_eq!(
+        parsed_root
+            .descendants(&parsed)
+            .map(|id| parsed[id].get().as_str())
+            .collect::<Vec<_>>(),
+        root.descendants(&arena)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        parsed_root
+            .descendants(&parsed)
+            .map(|id| id.children(&parsed).count())
+            .collect::<Vec<_>>(),
+        &[2, 0, 2, 0, 0]
+    );
+
+    let (parsed, parsed_root) = Arena::from_sexpr(" ( a\n( b )(c) ) ").unwrap();
+    assert_eq!(parsed_root.to_sexpr(&parsed), "(a (b) (c))");
+
+    assert_eq!(Arena::from_sexpr("").unwrap_err(), ParseError::Empty);
+    assert_eq!(Arena::from_sexpr("  ").unwrap_err(), ParseError::Empty);
+    assert_eq!(
+        Arena::from_sexpr("(a (b)").unwrap_err(),
+        ParseError::Unclosed
+    );
+    assert_eq!(Arena::from_sexpr("(a\\").unwrap_err(), ParseError::Unclosed);
+    assert_eq!(
+        Arena::from_sexpr("(a))").unwrap_err(),
+        ParseError::Unexpected(3)
+    );
+    assert_eq!(
+        Arena::from_sexpr("(a b)").unwrap_err(),
+        ParseError::Unexpected(3)
+    );
+    assert_eq!(
+        Arena::from_sexpr("(a) (b)").unwrap_err(),
+        ParseError::Unexpected(4)
+    );
+    assert_eq!(
+        Arena::from_sexpr("a").unwrap_err(),
+        ParseError::Unexpected(0)
+    );
+}
+
+#[test]
+fn descendants_rev() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    n1_2_1.append_value("1_2_1_1", &mut arena);
+    n1_2.append_value("1_2_2", &mut arena);
+    arena.new_node("2");
+
+    for id in n1.descendants(&arena) {
+        let mut forward = id.descendants(&arena).collect::<Vec<_>>();
+        forward.reverse();
+        assert_eq!(id.descendants(&arena).rev().collect::<Vec<_>>(), forward);
+
+        let mut edges = id.traverse(&arena).collect::<Vec<_>>();
+        edges.reverse();
+        assert_eq!(id.traverse(&arena).rev().collect::<Vec<_>>(), edges);
+    }
+
+    // Both ends meet in the middle without yielding a node twice.
+    let all = n1.descendants(&arena).collect::<Vec<_>>();
+    let mut iter = n1.descendants(&arena);
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some(id) = iter.next() {
+        front.push(id);
+        match iter.next_back() {
+            Some(id) => back.push(id),
+            None => break,
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, all);
+}
+
+#[test]
+fn max_width() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    n1_2.append_value("1_2_1", &mut arena);
+    n1_2.append_value("1_2_2", &mut arena);
+    n1_1_2.append_value("1_1_2_1", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     |       `-- 1_1_2_1
+    //     `-- 1_2
+    //         |-- 1_2_1
+    //         `-- 1_2_2
+
+    assert_eq!(n1.max_width(&arena), 4);
+    assert_eq!(n1_1.max_width(&arena), 2);
+    assert_eq!(n1_1_2.max_width(&arena), 1);
+    assert_eq!(arena.new_node("2").max_width(&arena), 1);
+}
+
+#[test]
+fn adjacency() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let removed = n1_2.append_value("removed", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    removed.remove(&mut arena);
+
+    let adjacency = arena.adjacency();
+    let live = arena.iter().filter(|node| !node.is_removed()).count();
+    assert_eq!(adjacency.len(), live);
+    assert_eq!(
+        adjacency.values().map(Vec::len).sum::<usize>(),
+        live - arena.roots().count()
+    );
+    assert_eq!(adjacency[&n1], &[n1_1, n1_2]);
+    assert_eq!(adjacency[&n1_1], &[n1_1_1]);
+    assert!(adjacency[&n1_2].is_empty());
+    assert_eq!(adjacency[&n2], &[n2_1]);
+    assert!(!adjacency.contains_key(&removed));
+}
+
+#[test]
+fn descendants_with_sibling_count() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    for data in ["1_1_1", "1_1_2", "1_1_3", "1_1_4"] {
+        n1_1.append_value(data, &mut arena);
+    }
+    n1_2.append_value("1_2_1", &mut arena);
+
+    for (id, count) in n1.descendants_with_sibling_count(&arena) {
+        let expected = match arena[id].parent() {
+            Some(parent) => parent.children(&arena).count(),
+            None => 1,
+        };
+        assert_eq!(count, expected);
+        for sibling in id.following_siblings(&arena) {
+            assert_eq!(
+                n1.descendants_with_sibling_count(&arena)
+                    .find(|&(other, _)| other == sibling)
+                    .map(|(_, count)| count),
+                Some(count)
+            );
+        }
+    }
+    assert_eq!(
+        n1_1.descendants_with_sibling_count(&arena)
+            .map(|(_, count)| count)
+            .collect::<Vec<_>>(),
+        &[2, 4, 4, 4, 4]
+    );
+}
+
+#[test]
+fn depth_cache() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_1_1 = n1_1_1.append_value("1_1_1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    arena.enable_depth_cache();
+
+    let assert_depths = |arena: &Arena<&str>| {
+        for node in arena.iter().filter(|node| !node.is_removed()) {
+            let id = arena.get_node_id(node).unwrap();
+            assert_eq!(id.depth(arena), id.ancestors(arena).count() - 1);
+        }
+    };
+    assert_depths(&arena);
+
+    n1_1_1.detach(&mut arena);
+    assert_depths(&arena);
+    n2_1.append(n1_1_1, &mut arena);
+    assert_depths(&arena);
+    assert_eq!(n1_1_1_1.depth(&arena), 3);
+    n1_2.prepend(n2, &mut arena);
+    assert_depths(&arena);
+    assert_eq!(n1_1_1_1.depth(&arena), 5);
+    n1_1.insert_after(n2_1, &mut arena);
+    assert_depths(&arena);
+    n2_1.move_before(n1_1, &mut arena).unwrap();
+    assert_depths(&arena);
+    n2_1.remove(&mut arena);
+    assert_depths(&arena);
+    assert_eq!(n1_1_1_1.depth(&arena), 2);
+    let n3 = n1_1_1_1.append_value("3", &mut arena);
+    assert_depths(&arena);
+    assert_eq!(n3.depth(&arena), 3);
+    n1_1.remove_subtree(&mut arena);
+    let n4 = arena.new_node("4");
+    assert_depths(&arena);
+    assert_eq!(n4.depth(&arena), 0);
+
+    let cloned = arena.clone();
+    assert_depths(&cloned);
+    arena.disable_depth_cache();
+    assert_depths(&arena);
+
+    // The cache is left out of equality and of the `Debug` output.
+    let uncached = arena.clone();
+    arena.enable_depth_cache();
+    assert_eq!(arena, uncached);
+    assert_eq!(format!("{arena:?}"), format!("{uncached:?}"));
+
+    // Restoring a snapshot taken without the cache keeps it up to date.
+    let snapshot = uncached.snapshot();
+    n1.append(n4, &mut arena);
+    arena.restore(&snapshot);
+    assert_depths(&arena);
+    n1.append(n4, &mut arena);
+    assert_depths(&arena);
+    assert_eq!(n4.depth(&arena), 1);
+}
+
+#[test]
+#[allow(clippy::double_ended_iterator_last)]
+fn children_last() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    assert_eq!(n1.children(&arena).last(), None);
+
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    assert_eq!(n1.children(&arena).last(), Some(n1_1));
+
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    assert_eq!(n1.children(&arena).last(), Some(n1_3));
+    assert_eq!(n1.children(&arena).rev().last(), Some(n1_1));
+
+    // Partially consumed iterators.
+    let mut iter = n1.children(&arena);
+    iter.next();
+    assert_eq!(iter.last(), Some(n1_3));
+    let mut iter = n1.children(&arena);
+    iter.next_back();
+    assert_eq!(iter.last(), Some(n1_2));
+    let mut iter = n1.children(&arena);
+    iter.next();
+    iter.next_back();
+    iter.next();
+    assert_eq!(iter.last(), None);
+
+    assert_eq!(n1_1.following_siblings(&arena).last(), Some(n1_3));
+    assert_eq!(n1_3.preceding_siblings(&arena).last(), Some(n1_1));
+
+    // Siblings of parentless nodes.
+    let lone = arena.new_node("lone");
+    assert_eq!(lone.following_siblings(&arena).last(), Some(lone));
+    assert_eq!(lone.preceding_siblings(&arena).last(), Some(lone));
+    assert_eq!(n1.following_siblings(&arena).last(), Some(n1));
+    let r1 = arena.new_node("r1");
+    let r2 = arena.new_node("r2");
+    let r3 = arena.new_node("r3");
+    r1.insert_after(r2, &mut arena);
+    r2.insert_after(r3, &mut arena);
+    assert_eq!(r1.following_siblings(&arena).last(), Some(r3));
+    assert_eq!(r3.preceding_siblings(&arena).last(), Some(r1));
+    let mut iter = r1.following_siblings(&arena);
+    iter.next();
+    assert_eq!(iter.last(), Some(r3));
+}
+
+#[test]
+fn node_bitset() {
+    let mut arena = Arena::new();
+    let ids = (0..150).map(|i| arena.new_node(i)).collect::<Vec<_>>();
+    let marked = [ids[0], ids[3], ids[63], ids[64], ids[127], ids[149]];
+
+    let mut set = arena.node_bitset();
+    assert_eq!(set.iter(&arena).count(), 0);
+    for &id in marked.iter().rev() {
+        set.set(id);
+    }
+    set.set(ids[64]);
+    assert_eq!(set.iter(&arena).collect::<Vec<_>>(), marked);
+    assert!(ids
+        .iter()
+        .all(|&id| set.contains(id) == marked.contains(&id)));
+
+    set.clear(ids[63]);
+    set.clear(ids[1]);
+    assert!(!set.contains(ids[63]));
+    assert_eq!(
+        set.iter(&arena).collect::<Vec<_>>(),
+        &[ids[0], ids[3], ids[64], ids[127], ids[149]]
+    );
+
+    // Removed nodes are skipped, new nodes grow the set.
+    ids[3].remove(&mut arena);
+    assert!(!set.iter(&arena).any(|id| id == ids[3]));
+    let reused = arena.new_node(3);
+    assert!(set.contains(reused));
+    let trailing = (0..100).map(|i| arena.new_node(i)).last().unwrap();
+    set.set(trailing);
+    assert!(set.contains(trailing));
+    assert_eq!(
+        set.iter(&arena).collect::<Vec<_>>(),
+        &[ids[0], reused, ids[64], ids[127], ids[149], trailing]
+    );
+}
+
+#[test]
+fn coalesce_children() {
+    let mut arena = Arena::new();
+    let p = arena.new_node(String::from("p"));
+    for text in ["a", "b", "c"] {
+        p.append_value(String::from(text), &mut arena);
+    }
+    let em = p.append_value(String::from("em"), &mut arena);
+    em.append_value(String::from("d"), &mut arena);
+    em.append_value(String::from("e"), &mut arena);
+    for text in ["f", "#", "g", "h"] {
+        p.append_value(String::from(text), &mut arena);
+    }
+
+    // Merge text leaves, but never across a `#` separator.
+    let mut calls = 0;
+    p.coalesce_children(&mut arena, |left, right| {
+        calls += 1;
+        if left == "#" || right == "#" {
+            return false;
+        }
+        left.push_str(&std::mem::take(right));
+        true
+    });
+    // a+b, ab+c, f+#, #+g, g+h
+    assert_eq!(calls, 5);
+    let texts = |id: indextree::NodeId, arena: &Arena<String>| {
+        id.children(arena)
+            .map(|child| arena[child].get().clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(texts(p, &arena), ["abc", "em", "f", "#", "gh"]);
+    // Only the children of the node itself are coalesced.
+    assert_eq!(texts(em, &arena), ["d", "e"]);
+    assert_eq!(arena.iter().filter(|node| !node.is_removed()).count(), 8);
+}
+
+#[test]
+fn get_many() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+
+    let nodes = arena.get_many(&[n1_2, n1, n1_1]).unwrap();
+    assert_eq!(
+        nodes.iter().map(|node| *node.get()).collect::<Vec<_>>(),
+        &["1_2", "1", "1_1"]
+    );
+    assert_eq!(nodes[0].parent(), Some(n1));
+    assert!(arena.get_many(&[]).unwrap().is_empty());
+
+    n1_1.remove(&mut arena);
+    assert!(arena.get_many(&[n1, n1_1]).is_none());
+    // The stale id still fails once its slot is reused.
+    let n2 = arena.new_node("2");
+    assert!(arena.get_many(&[n2, n1_1]).is_none());
+    assert_eq!(arena.get_many(&[n2]).unwrap()[0].get(), &"2");
+}
+
+#[test]
+fn walk_events() {
+    use indextree::WalkEvent::{Down, Next, Up};
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   |   `-- 1_2_1_1
+    //     |   `-- 1_2_2
+    //     `-- 1_3
+
+    assert_eq!(
+        n1.walk_events(&arena).collect::<Vec<_>>(),
+        &[
+            Down(n1),
+            Down(n1_1),
+            Next(n1_2),
+            Down(n1_2_1),
+            Down(n1_2_1_1),
+            Up(n1_2_1),
+            Next(n1_2_2),
+            Up(n1_2),
+            Next(n1_3),
+            Up(n1),
+        ]
+    );
+    // The walk stays within the subtree.
+    assert_eq!(
+        n1_2.walk_events(&arena).collect::<Vec<_>>(),
+        &[
+            Down(n1_2),
+            Down(n1_2_1),
+            Down(n1_2_1_1),
+            Up(n1_2_1),
+            Next(n1_2_2),
+            Up(n1_2)
+        ]
+    );
+    assert_eq!(n1_3.walk_events(&arena).collect::<Vec<_>>(), &[Down(n1_3)]);
+}
+
+#[test]
+fn next_in_preorder() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n2 = arena.new_node("2");
+    // arena
+    // |-- 1
+    // |   |-- 1_1
+    // |   |-- 1_2
+    // |   |   |-- 1_2_1
+    // |   |   |   `-- 1_2_1_1
+    // |   |   `-- 1_2_2
+    // |   `-- 1_3
+    // `-- 2
+
+    let steps: Vec<_> =
+        core::iter::successors(Some(n1), |id| id.next_in_preorder(n1, &arena)).collect();
+    assert_eq!(steps, &[n1, n1_1, n1_2, n1_2_1, n1_2_1_1, n1_2_2, n1_3]);
+    assert_eq!(
+        steps,
+        n1.descendants(&arena).collect::<Vec<_>>(),
+        "stepping yields the same nodes as `descendants`"
+    );
+    // The successor stays within the subtree of the given root.
+    let steps: Vec<_> =
+        core::iter::successors(Some(n1_2), |id| id.next_in_preorder(n1_2, &arena)).collect();
+    assert_eq!(steps, &[n1_2, n1_2_1, n1_2_1_1, n1_2_2]);
+    assert_eq!(n1_3.next_in_preorder(n1, &arena), None);
+    assert_eq!(n2.next_in_preorder(n2, &arena), None);
+}
+
+#[test]
+fn prev_in_preorder() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   |   `-- 1_2_1_1
+    //     |   `-- 1_2_2
+    //     `-- 1_3
+
+    let steps: Vec<_> =
+        core::iter::successors(Some(n1_3), |id| id.prev_in_preorder(n1, &arena)).collect();
+    assert_eq!(steps, &[n1_3, n1_2_2, n1_2_1_1, n1_2_1, n1_2, n1_1, n1]);
+    // The predecessor stays within the subtree of the given root.
+    assert_eq!(n1_2.prev_in_preorder(n1_2, &arena), None);
+    assert_eq!(n1_2_1.prev_in_preorder(n1_2, &arena), Some(n1_2));
+
+    // `next_in_preorder` and `prev_in_preorder` are inverses.
+    for root in [n1, n1_2] {
+        for id in root.descendants(&arena) {
+            if let Some(next) = id.next_in_preorder(root, &arena) {
+                assert_eq!(next.prev_in_preorder(root, &arena), Some(id));
+            }
+            if let Some(prev) = id.prev_in_preorder(root, &arena) {
+                assert_eq!(prev.next_in_preorder(root, &arena), Some(id));
+            }
+        }
+    }
+}
+
+#[test]
+fn dirty_nodes() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    assert_eq!(arena.dirty_nodes().next(), None);
+
+    // Replacing the data marks the node, while mutable borrows do not.
+    let unmarked = arena.clone();
+    *arena.get_mut(n1_2).unwrap().get_mut() = "one two";
+    assert!(!n1_2.is_dirty(&arena));
+    n1_2.mark_dirty(&mut arena);
+    arena.set_data(n1_3, "one three").unwrap();
+    n1.mark_dirty(&mut arena);
+    assert!(n1.is_dirty(&arena));
+    assert!(!n1_1.is_dirty(&arena));
+    assert!(n1_2.is_dirty(&arena));
+    assert_eq!(arena.dirty_nodes().collect::<Vec<_>>(), &[n1, n1_2, n1_3]);
+
+    // Removed nodes are unmarked, and their slots are reused unmarked.
+    n1_3.remove(&mut arena);
+    assert!(!n1_3.is_dirty(&arena));
+    let n1_4 = n1.append_value("1_4", &mut arena);
+    assert!(!n1_4.is_dirty(&arena));
+    assert_eq!(arena.dirty_nodes().collect::<Vec<_>>(), &[n1, n1_2]);
+
+    arena.clear_dirty();
+    assert!(!n1.is_dirty(&arena));
+    assert!(!n1_2.is_dirty(&arena));
+    assert_eq!(arena.dirty_nodes().next(), None);
+
+    // The flags are not part of the equality of arenas.
+    let mut marked = unmarked.clone();
+    n1.mark_dirty(&mut marked);
+    assert_eq!(marked, unmarked);
+    let _ = marked.get_mut(n1);
+    assert_eq!(marked, unmarked);
+}
+
+#[test]
+fn balanced_from_slice() {
+    let values: Vec<_> = (0..40).collect();
+    for (len, branching, height) in [
+        (1, 2, 1),
+        (7, 2, 3),
+        (8, 2, 4),
+        (13, 3, 3),
+        (40, 3, 4),
+        (5, 1, 5),
+    ] {
+        let (arena, root) = Arena::balanced_from_slice(&values[..len], branching);
+        assert_eq!(arena.count(), len);
+        assert_eq!(root.descendants(&arena).count(), len);
+        let levels = root.levels(&arena);
+        assert_eq!(levels.len(), height, "{len} values, branching {branching}");
+        // Values are assigned in level order, and all levels but the last are
+        // full.
+        let level_order: Vec<_> = levels
+            .iter()
+            .flatten()
+            .map(|&id| *arena[id].get())
+            .collect();
+        assert_eq!(level_order, &values[..len]);
+        for (depth, level) in levels[..height - 1].iter().enumerate() {
+            assert_eq!(level.len(), branching.pow(depth as u32));
+        }
+    }
+}
+
+#[test]
+fn leaf_count() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    n1_2_1.append_value("1_2_1_1", &mut arena);
+    n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   |   `-- 1_2_1_1 *
+    //     |   `-- 1_2_2 *
+    //     `-- 1_3 *
+
+    assert_eq!(n1.leaf_count(&arena), 4);
+    assert_eq!(n1_2.leaf_count(&arena), 2);
+    assert_eq!(n1_2_1.leaf_count(&arena), 1);
+    assert_eq!(n1_1.leaf_count(&arena), 1);
+    assert_eq!(n1_3.leaf_count(&arena), 1);
+}
+
+#[test]
+fn frontier() {
+    use std::collections::HashSet;
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   |   `-- 1_2_1_1
+    //     |   `-- 1_2_2
+    //     `-- 1_3
+
+    let frontier = |expanded: &HashSet<_>| {
+        n1.frontier(&arena, |id, _| expanded.contains(&id))
+            .collect::<Vec<_>>()
+    };
+    let mut expanded: HashSet<_> = n1.descendants(&arena).collect();
+    assert!(frontier(&expanded).is_empty());
+
+    // Collapsing an interior node makes it the frontier, hiding its subtree.
+    expanded.remove(&n1_2_1);
+    assert_eq!(frontier(&expanded), &[n1_2_1]);
+    expanded.remove(&n1_2);
+    assert_eq!(frontier(&expanded), &[n1_2]);
+
+    // Expanding it again with its children collapsed makes them the frontier.
+    expanded.insert(n1_2);
+    expanded.remove(&n1_2_2);
+    assert_eq!(frontier(&expanded), &[n1_2_1, n1_2_2]);
+
+    expanded.remove(&n1_1);
+    expanded.remove(&n1_3);
+    assert_eq!(frontier(&expanded), &[n1_1, n1_2_1, n1_2_2, n1_3]);
+
+    // Nodes below the frontier are not visited.
+    let mut visited = Vec::new();
+    let count = n1
+        .frontier(&arena, |id, _| {
+            visited.push(id);
+            id == n1
+        })
+        .count();
+    assert_eq!(count, 3);
+    assert_eq!(visited, &[n1, n1_1, n1_2, n1_3]);
+    assert!(!visited.contains(&n1_2_1_1));
+
+    // The frontier of a collapsed node is empty.
+    assert_eq!(n1.frontier(&arena, |_, _| false).next(), None);
+}
+
+#[test]
+fn snapshot_restore() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1".to_string());
+    let n1_1 = n1.append_value("1_1".to_string(), &mut arena);
+    let n1_2 = n1.append_value("1_2".to_string(), &mut arena);
+    n1_2.append_value("1_2_1".to_string(), &mut arena);
+    let n2 = arena.new_node("2".to_string());
+    n1_1.remove(&mut arena);
+    let traversal = |arena: &Arena<String>| {
+        arena
+            .roots()
+            .flat_map(|root| root.traverse(arena))
+            .collect::<Vec<_>>()
+    };
+    let saved = arena.clone();
+    let expected = traversal(&arena);
+    let snapshot = arena.snapshot();
+
+    // Structural and data mutations are rolled back.
+    n2.append(n1_2, &mut arena);
+    *arena[n1].get_mut() = "one".to_string();
+    let reused = arena.new_node("reused".to_string());
+    arena.new_node("3".to_string());
+    n1.remove_subtree(&mut arena);
+    arena.restore(&snapshot);
+    assert_eq!(traversal(&arena), expected);
+    assert_eq!(arena, saved);
+    assert_eq!(*arena[n1].get(), "1");
+    assert!(n1_1.is_removed(&arena));
+    assert!(reused.is_removed(&arena));
+
+    // The free slots are restored as well, and the snapshot can be restored
+    // again.
+    let reused = arena.new_node("reused".to_string());
+    assert_eq!(arena.count(), 5);
+    arena.restore(&snapshot);
+    assert_eq!(traversal(&arena), expected);
+    assert!(reused.is_removed(&arena));
+}
+
+#[test]
+fn snapshot_restore_stale_ids() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let removed = arena.new_node("removed");
+    removed.remove(&mut arena);
+    let snapshot = arena.snapshot();
+
+    // A node pushed at the end of the storage after the snapshot.
+    let n2 = arena.new_node("2");
+    let pushed = arena.new_node("pushed");
+    assert_eq!(usize::from(n2), usize::from(removed));
+    arena.restore(&snapshot);
+    let n3 = arena.new_node("3");
+    let n4 = arena.new_node("4");
+    assert_eq!(usize::from(n4), usize::from(pushed));
+    assert_ne!(n2, n3);
+    assert_ne!(pushed, n4);
+    assert!(n2.is_removed(&arena));
+    assert!(pushed.is_removed(&arena));
+    assert_eq!(*arena[n3].get(), "3");
+
+    // A node in a slot that was live when the snapshot was taken.
+    let snapshot = arena.snapshot();
+    n1.remove(&mut arena);
+    let n5 = arena.new_node("5");
+    assert_eq!(usize::from(n5), usize::from(n1));
+    arena.restore(&snapshot);
+    assert!(!n1.is_removed(&arena));
+    assert!(n5.is_removed(&arena));
+    n1.remove(&mut arena);
+    let n6 = arena.new_node("6");
+    assert_eq!(usize::from(n6), usize::from(n1));
+    assert_ne!(n5, n6);
+    assert!(n5.is_removed(&arena));
+}
+
+#[test]
+fn centroid() {
+    // Path graphs, the centroid is the middle node.
+    for len in 1..8 {
+        let mut arena = Arena::new();
+        let root = arena.new_node(0);
+        let mut path = vec![root];
+        for i in 1..len {
+            let last = *path.last().unwrap();
+            path.push(last.append_value(i, &mut arena));
+        }
+        assert_eq!(root.centroid(&arena), path[(len - 1) / 2], "path of {len}");
+        // Only the subtree of the given node is considered.
+        assert_eq!(path[len - 1].centroid(&arena), path[len - 1]);
+        if len > 2 {
+            assert_eq!(path[1].centroid(&arena), path[1 + (len - 2) / 2]);
+        }
+    }
+
+    // No component exceeds half of the nodes once the centroid is removed.
+    let (arena, root) = Arena::balanced_from_slice(&(0..20).collect::<Vec<_>>(), 3);
+    let centroid = root.centroid(&arena);
+    assert_eq!(*arena[centroid].get(), 1);
+    for child in centroid.children(&arena) {
+        assert!(2 * child.descendants(&arena).count() <= 20);
+    }
+    assert!(2 * (20 - centroid.descendants(&arena).count()) <= 20);
+}
+
+#[test]
+fn with_root() {
+    let (arena, root) = Arena::with_root("root");
+    assert_eq!(*arena[root].get(), "root");
+    assert_eq!(arena.count(), 1);
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[root]);
+    assert_eq!(arena[root].parent(), None);
+    assert_eq!(arena[root].first_child(), None);
+}
+
+#[test]
+fn diameter() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2
+    //     |   |-- 1_2_1
+    //     |   |   `-- 1_2_1_1
+    //     |   `-- 1_2_2
+    //     `-- 1_3
+
+    // 1_2_1_1 -> 1_2_1 -> 1_2 -> 1 -> 1_1
+    assert_eq!(n1.diameter(&arena), 4);
+    // 1_2_1_1 -> 1_2_1 -> 1_2 -> 1_2_2
+    assert_eq!(n1_2.diameter(&arena), 3);
+    assert_eq!(n1_2_1.diameter(&arena), 1);
+    assert_eq!(n1_2_1_1.diameter(&arena), 0);
+
+    // The longest path may avoid the root of the subtree.
+    let n1_2_2_1 = n1_2_2.append_value("1_2_2_1", &mut arena);
+    n1_2_2_1.append_value("1_2_2_1_1", &mut arena);
+    assert_eq!(n1_2.diameter(&arena), 5);
+    assert_eq!(n1.diameter(&arena), 5);
+    assert_eq!(n1_1.diameter(&arena), 0);
+    assert_eq!(n1_3.diameter(&arena), 0);
+}
+
+#[test]
+fn contains_value() {
+    use std::cell::Cell;
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    n1_2.append_value("1_2_1", &mut arena);
+    n1_2.append_value("1_2_2", &mut arena);
+    n1.append_value("1_3", &mut arena);
+    let n2 = arena.new_node("2");
+
+    assert!(n1.contains_value(&arena, &"1"));
+    assert!(n1.contains_value(&arena, &"1_2_2"));
+    assert!(n1_2.contains_value(&arena, &"1_2_1"));
+    assert!(!n1.contains_value(&arena, &"2"));
+    assert!(!n1_2.contains_value(&arena, &"1_1"));
+    assert!(!n1_2.contains_value(&arena, &"1_3"));
+    assert!(!n1_1.contains_value(&arena, &"1_2"));
+    assert!(n2.contains_value(&arena, &"2"));
+
+    // The search stops at the first match.
+    #[derive(Debug)]
+    struct Counted<'a>(u32, &'a Cell<usize>);
+    impl PartialEq for Counted<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.1.set(self.1.get() + 1);
+            self.0 == other.0
+        }
+    }
+    let comparisons = Cell::new(0);
+    let mut arena = Arena::new();
+    let root = arena.new_node(Counted(0, &comparisons));
+    for i in 1..10 {
+        root.append_value(Counted(i, &comparisons), &mut arena);
+    }
+    assert!(root.contains_value(&arena, &Counted(3, &comparisons)));
+    assert_eq!(comparisons.get(), 4);
+}
+
+#[cfg(feature = "deser")]
+#[test]
+fn serialize_node_id() {
+    let mut arena = Arena::new();
+    arena.clear();
+    let id = arena.new_node("1");
+
+    // The generation is left out, keeping the format of older versions.
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, r#"{"index1":1,"stamp":0}"#);
+    let deserialized: NodeId = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, id);
+    assert!(!deserialized.is_current(&arena));
+}
+
+#[cfg(feature = "deser")]
+#[test]
+fn serialize_canonical() {
+    fn to_json(arena: &Arena<&str>) -> String {
+        let mut json = Vec::new();
+        arena
+            .serialize_canonical(&mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    // Forest of three trees.
+    let mut first = Arena::new();
+    let x = first.new_node("x");
+    x.append_value("x_1", &mut first);
+    let y = first.new_node("y");
+    let y_1 = y.append_value("y_1", &mut first);
+    y_1.append_value("y_1_1", &mut first);
+    y.append_value("y_2", &mut first);
+    first.new_node("z");
+
+    // Same forest, built in a different order, with tombstones and with the
+    // roots in a different storage-order.
+    let mut second = Arena::new();
+    let removed = second.new_node("removed");
+    second.new_node("z");
+    let y_2 = second.new_node("y_2");
+    let y = second.new_node("y");
+    removed.remove(&mut second);
+    let x = second.new_node("x");
+    let y_1 = y.append_value("y_1", &mut second);
+    y_1.insert_after(y_2, &mut second);
+    y_1.append_value("y_1_1", &mut second);
+    let x_1 = second.new_node("x_1");
+    x.append(x_1, &mut second);
+
+    assert_ne!(
+        first.roots().map(|id| first[id].get()).collect::<Vec<_>>(),
+        second
+            .roots()
+            .map(|id| second[id].get())
+            .collect::<Vec<_>>(),
+    );
+    let json = to_json(&first);
+    assert_eq!(json, to_json(&second));
+    assert_eq!(
+        json,
+        r#"[[null,"x"],[0,"x_1"],[null,"y"],[2,"y_1"],[3,"y_1_1"],[2,"y_2"],[null,"z"]]"#
+    );
+
+    // The output can be read back.
+    let items: Vec<(Option<usize>, String)> = serde_json::from_str(&json).unwrap();
+    let (restored, _) = Arena::try_from_parent_ids(items).unwrap();
+    assert_eq!(restored.roots().count(), 3);
+}
@@ -1,6 +1,7 @@
 use indextree::{
     Arena,
     NodeEdge::{End, Start},
+    RemovalEffect,
 };
 
 #[test]
@@ -386,3 +387,467 @@ fn middle_child_with_multiple_children() {
         ]
     );
 }
+
+#[test]
+fn remove_subtree_count() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_1_2_1 = n1_1_2.append_value("1_1_2_1", &mut arena);
+    let n1_1_3 = n1_1.append_value("1_1_3", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |   |-- 1_1_1
+    //     |   |-- 1_1_2
+    //     |   |   `-- 1_1_2_1
+    //     |   `-- 1_1_3
+    //     `-- 1_2
+    assert_eq!(n1_1.remove_subtree_count(&mut arena), 5);
+    for id in [n1_1, n1_1_1, n1_1_2, n1_1_2_1, n1_1_3] {
+        assert!(id.is_removed(&arena));
+    }
+    assert!(!n1.is_removed(&arena));
+    assert!(!n1_2.is_removed(&arena));
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2]);
+}
+
+#[test]
+fn collapse_into() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_1_1 = n1_1_1.append_value("1_1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |   |-- 1_1_1
+    //     |   |   `-- 1_1_1_1
+    //     |   `-- 1_1_2
+    //     `-- 1_2
+    assert_eq!(n1_1.collapse_into("summary", &mut arena), n1_1);
+    // arena
+    // `-- 1
+    //     |-- summary
+    //     `-- 1_2
+    assert_eq!(*arena[n1_1].get(), "summary");
+    assert!(arena[n1_1].first_child().is_none());
+    assert!(arena[n1_1].last_child().is_none());
+    for id in [n1_1_1, n1_1_1_1, n1_1_2] {
+        assert!(id.is_removed(&arena));
+    }
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_1, n1_2]);
+
+    // Collapsing a leaf only replaces its data.
+    n1_2.collapse_into("leaf", &mut arena);
+    assert_eq!(*arena[n1_2].get(), "leaf");
+    assert_eq!(arena.count(), 6);
+}
+
+#[test]
+fn move_into_new_arena() {
+    #[derive(Debug, PartialEq)]
+    struct NotClone(&'static str);
+
+    let mut arena = Arena::new();
+    let n1 = arena.new_node(NotClone("1"));
+    let n1_1 = n1.append_value(NotClone("1_1"), &mut arena);
+    let n1_1_1 = n1_1.append_value(NotClone("1_1_1"), &mut arena);
+    let n1_1_2 = n1_1.append_value(NotClone("1_1_2"), &mut arena);
+    let n1_1_2_1 = n1_1_2.append_value(NotClone("1_1_2_1"), &mut arena);
+    let n1_2 = n1.append_value(NotClone("1_2"), &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     |       `-- 1_1_2_1
+    //     `-- 1_2
+
+    let (new_arena, root) = n1_1.move_into_new_arena(&mut arena);
+    for id in [n1_1, n1_1_1, n1_1_2, n1_1_2_1] {
+        assert!(id.is_removed(&arena));
+    }
+    assert_eq!(n1.children(&arena).collect::<Vec<_>>(), &[n1_2]);
+
+    assert_eq!(new_arena.count(), 4);
+    assert!(new_arena[root].parent().is_none());
+    assert_eq!(
+        root.traverse(&new_arena)
+            .map(|edge| match edge {
+                Start(id) => (true, new_arena[id].get().0),
+                End(id) => (false, new_arena[id].get().0),
+            })
+            .collect::<Vec<_>>(),
+        &[
+            (true, "1_1"),
+            (true, "1_1_1"),
+            (false, "1_1_1"),
+            (true, "1_1_2"),
+            (true, "1_1_2_1"),
+            (false, "1_1_2_1"),
+            (false, "1_1_2"),
+            (false, "1_1"),
+        ]
+    );
+
+    // The freed slots are reused by the source arena.
+    let n3 = arena.new_node(NotClone("3"));
+    assert!(!n3.is_removed(&arena));
+    assert_eq!(arena[n3].get(), &NotClone("3"));
+}
+
+#[test]
+fn drop_tree() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    n2_1.append_value("2_1_1", &mut arena);
+    assert_eq!(arena.count(), 5);
+
+    // The most recently built tree is at the storage tail.
+    assert_eq!(arena.drop_tree(n2), 3);
+    assert_eq!(arena.count(), 2);
+    assert_eq!(n1.descendants(&arena).collect::<Vec<_>>(), &[n1, n1_1]);
+
+    // A tree in the middle of the storage leaves tombstones.
+    let n3 = arena.new_node("3");
+    assert_eq!(arena.drop_tree(n1), 2);
+    assert_eq!(arena.count(), 3);
+    assert!(n1.is_removed(&arena));
+    assert!(!n3.is_removed(&arena));
+}
+
+#[test]
+fn drop_tree_keeps_other_tombstones() {
+    let mut arena = Arena::new();
+    let x = arena.new_node("x");
+    x.append_value("x_1", &mut arena);
+    let y = arena.new_node("y");
+    let z = arena.new_node("z");
+    y.remove(&mut arena);
+    z.remove(&mut arena);
+    assert_eq!(arena.count(), 4);
+
+    // The slots of `y` and `z` are not freed by this call.
+    assert_eq!(arena.drop_tree(x), 2);
+    assert_eq!(arena.count(), 4);
+
+    // Only the trailing slots of the dropped tree are reclaimed.
+    let mut arena = Arena::new();
+    let y = arena.new_node("y");
+    let x = arena.new_node("x");
+    x.append_value("x_1", &mut arena);
+    y.remove(&mut arena);
+    assert_eq!(arena.drop_tree(x), 2);
+    assert_eq!(arena.count(), 1);
+}
+
+#[test]
+fn first_child_with_multiple_children() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     `-- 1_2
+    n1_1.remove(&mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1_1
+    //     |-- 1_1_2
+    //     `-- 1_2
+    assert_eq!(
+        n1.children(&arena).collect::<Vec<_>>(),
+        &[n1_1_1, n1_1_2, n1_2]
+    );
+    assert_eq!(arena[n1].first_child(), Some(n1_1_1));
+}
+
+#[test]
+fn last_child_with_multiple_children() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     `-- 1_2 *
+    //         |-- 1_2_1
+    //         `-- 1_2_2
+    n1_2.remove(&mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1
+    //     |-- 1_2_1
+    //     `-- 1_2_2
+    assert_eq!(
+        n1.children(&arena).collect::<Vec<_>>(),
+        &[n1_1, n1_2_1, n1_2_2]
+    );
+    assert_eq!(arena[n1].last_child(), Some(n1_2_2));
+}
+
+#[test]
+fn splice_at_end() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_1 *
+    //     |   |-- 1_1_1
+    //     |   `-- 1_1_2
+    //     |-- 1_2
+    //     |   `-- 1_2_1
+    //     `-- 1_3
+    n1_1.remove_splice_at_end(&mut arena);
+    // arena
+    // `-- 1
+    //     |-- 1_2
+    //     |   `-- 1_2_1
+    //     |-- 1_3
+    //     |-- 1_1_1
+    //     `-- 1_1_2
+    assert!(n1_1.is_removed(&arena));
+    assert_eq!(
+        n1.children(&arena).collect::<Vec<_>>(),
+        &[n1_2, n1_3, n1_1_1, n1_1_2]
+    );
+    assert_eq!(arena[n1].last_child(), Some(n1_1_2));
+    assert_eq!(arena[n1_1_1].parent(), Some(n1));
+    assert_eq!(arena[n1_2_1].parent(), Some(n1_2));
+
+    // Removing the last child behaves like `remove`.
+    n1_1_2.append_value("1_1_2_1", &mut arena);
+    n1_1_2.remove_splice_at_end(&mut arena);
+    assert_eq!(n1.children(&arena).count(), 4);
+
+    // Leaves are simply removed.
+    n1_3.remove_splice_at_end(&mut arena);
+    n1_2.remove_splice_at_end(&mut arena);
+    assert_eq!(
+        n1.descendants(&arena)
+            .skip(1)
+            .map(|id| *arena[id].get())
+            .collect::<Vec<_>>(),
+        &["1_1_1", "1_1_2_1", "1_2_1"]
+    );
+
+    // Toplevel nodes splice their children after their last sibling.
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    let n2_2 = n2.append_value("2_2", &mut arena);
+    n2_1.append_value("2_1_1", &mut arena);
+    n2.remove_splice_at_end(&mut arena);
+    assert_eq!(
+        n2_1.following_siblings(&arena).collect::<Vec<_>>(),
+        &[n2_1, n2_2]
+    );
+    assert!(arena[n2_1].parent().is_none());
+}
+
+#[test]
+fn remove_leaves() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_1_1 = n1_1_1.append_value("1_1_1_1", &mut arena);
+
+    // A path only loses its deepest node per pass.
+    assert_eq!(arena.remove_leaves(), &[n1_1_1_1]);
+    assert!(!n1_1_1.is_removed(&arena));
+    assert_eq!(arena[n1_1_1].first_child(), None);
+    assert_eq!(
+        n1.descendants(&arena).collect::<Vec<_>>(),
+        &[n1, n1_1, n1_1_1]
+    );
+
+    assert_eq!(arena.remove_leaves(), &[n1_1_1]);
+    assert_eq!(arena.remove_leaves(), &[n1_1]);
+    assert_eq!(arena.remove_leaves(), &[n1]);
+    assert!(arena.remove_leaves().is_empty());
+}
+
+#[cfg(feature = "retain-removed-data")]
+#[test]
+fn removed_data() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    assert_eq!(n1_1.removed_data(&arena), None);
+
+    n1_1.remove(&mut arena);
+    assert_eq!(n1_1.removed_data(&arena), Some(&"1_1"));
+    assert_eq!(n1_1_1.removed_data(&arena), None);
+
+    n1.remove_subtree(&mut arena);
+    assert_eq!(n1.removed_data(&arena), Some(&"1"));
+    assert_eq!(n1_1_1.removed_data(&arena), Some(&"1_1_1"));
+    assert_eq!(n1_1.removed_data(&arena), Some(&"1_1"));
+
+    // The first freed slot is reused, and its retained data is dropped.
+    let n2 = arena.new_node("2");
+    assert_eq!(n1_1.removed_data(&arena), None);
+    assert_eq!(arena[n2].get(), &"2");
+    assert_eq!(n1.removed_data(&arena), Some(&"1"));
+
+    // A removed reuse of the slot does not resurrect the old data.
+    n2.remove(&mut arena);
+    assert_eq!(n1_1.removed_data(&arena), None);
+    assert_eq!(n2.removed_data(&arena), Some(&"2"));
+}
+
+#[test]
+fn removal_effect() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_1_1 = n1_1.append_value("1_1_1", &mut arena);
+    let n1_1_2 = n1_1.append_value("1_1_2", &mut arena);
+
+    assert_eq!(
+        n1_1.removal_effect(&arena),
+        RemovalEffect::SpliceToParent(n1)
+    );
+    n1_1.remove(&mut arena);
+    assert_eq!(arena[n1_1_1].parent(), Some(n1));
+    assert_eq!(arena[n1_1_2].parent(), Some(n1));
+
+    assert_eq!(
+        n1.removal_effect(&arena),
+        RemovalEffect::PromoteChildrenToRoots
+    );
+    n1.remove(&mut arena);
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[n1_1_1, n1_1_2]);
+}
+
+#[test]
+fn prune_to_fixpoint() {
+    let mut arena = Arena::new();
+    let root = arena.new_node("box");
+    let b1 = root.append_value("box", &mut arena);
+    let b1_1 = b1.append_value("box", &mut arena);
+    let b1_1_1 = b1_1.append_value("box", &mut arena);
+    b1_1_1.append_value("box", &mut arena);
+    let b2 = root.append_value("box", &mut arena);
+    let b2_1 = b2.append_value("box", &mut arena);
+    let text = b2_1.append_value("text", &mut arena);
+    let b3 = root.append_value("box", &mut arena);
+    // arena
+    // `-- box
+    //     |-- box                  (empty chain)
+    //     |   `-- box
+    //     |       `-- box
+    //     |           `-- box
+    //     |-- box
+    //     |   `-- box
+    //     |       `-- text
+    //     `-- box                  (empty)
+
+    let calls = std::cell::Cell::new(0);
+    arena.prune_to_fixpoint(|id, arena| {
+        calls.set(calls.get() + 1);
+        *arena[id].get() == "box"
+            && arena[id].parent().is_some()
+            && arena[id].first_child().is_none()
+    });
+
+    assert_eq!(
+        root.descendants(&arena).collect::<Vec<_>>(),
+        &[root, b2, b2_1, text]
+    );
+    assert!(b1.is_removed(&arena));
+    assert!(b3.is_removed(&arena));
+    // One pass per level of the empty chain, plus a final pass removing
+    // nothing: 9 + 7 + 6 + 5 + 4 live nodes evaluated.
+    assert_eq!(calls.get(), 31);
+}
+
+#[test]
+fn keep_only() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n2 = arena.new_node("2");
+    let n2_1 = n2.append_value("2_1", &mut arena);
+    let capacity = arena.capacity();
+
+    arena.keep_only(n1_2);
+    assert_eq!(arena.roots().collect::<Vec<_>>(), &[n1_2]);
+    assert_eq!(
+        n1_2.descendants(&arena).collect::<Vec<_>>(),
+        &[n1_2, n1_2_1, n1_2_2]
+    );
+    for id in [n1, n1_1, n1_3, n2, n2_1] {
+        assert!(id.is_removed(&arena));
+    }
+    assert_eq!(arena.capacity(), capacity);
+
+    // The removed slots are reused before growing the storage.
+    let count = arena.count();
+    for i in 0..5 {
+        arena.new_node(["a", "b", "c", "d", "e"][i]);
+    }
+    assert_eq!(arena.count(), count);
+}
+
+#[test]
+fn truncate_depth() {
+    let mut arena = Arena::new();
+    let n1 = arena.new_node("1");
+    let n1_1 = n1.append_value("1_1", &mut arena);
+    let n1_2 = n1.append_value("1_2", &mut arena);
+    let n1_2_1 = n1_2.append_value("1_2_1", &mut arena);
+    let n1_2_1_1 = n1_2_1.append_value("1_2_1_1", &mut arena);
+    let n1_2_2 = n1_2.append_value("1_2_2", &mut arena);
+    let n1_3 = n1.append_value("1_3", &mut arena);
+    let n1_3_1 = n1_3.append_value("1_3_1", &mut arena);
+
+    // Nothing lies deeper than the deepest node.
+    assert!(n1.truncate_depth(&mut arena, 3).is_empty());
+    assert_eq!(n1.descendants(&arena).count(), 8);
+
+    assert_eq!(n1.truncate_depth(&mut arena, 1), &[n1_2_1, n1_2_2, n1_3_1]);
+    for id in [n1_1, n1_2, n1_3] {
+        assert_eq!(arena[id].first_child(), None);
+    }
+    for id in [n1_2_1, n1_2_1_1, n1_2_2, n1_3_1] {
+        assert!(id.is_removed(&arena));
+    }
+    assert_eq!(
+        n1.descendants(&arena).collect::<Vec<_>>(),
+        &[n1, n1_1, n1_2, n1_3]
+    );
+
+    assert_eq!(n1.truncate_depth(&mut arena, 0), &[n1_1, n1_2, n1_3]);
+    assert_eq!(n1.descendants(&arena).collect::<Vec<_>>(), &[n1]);
+}